@@ -0,0 +1,1003 @@
+use crate::{
+    Error, Popover,
+    components::save_file_dialog,
+    data::{DataFrameContainer, PendingTask},
+    plots::format_axis_value,
+};
+
+use egui::{
+    Align2, Color32, ComboBox, Context, Event, FontId, Pos2, Rect, Sense, Shape, Stroke, Ui,
+    UserData, Vec2, ViewportCommand,
+};
+use polars::prelude::*;
+use std::{collections::HashMap, fmt::Write, path::PathBuf, sync::Arc};
+use tokio::runtime::Handle;
+
+/// Maximum number of series drawn in a single chart.
+const MAX_SERIES: usize = 30;
+
+/// Colors assigned to the series, in order.
+const PALETTE: [Color32; 10] = [
+    Color32::from_rgb(31, 119, 180),
+    Color32::from_rgb(255, 127, 14),
+    Color32::from_rgb(44, 160, 44),
+    Color32::from_rgb(214, 39, 40),
+    Color32::from_rgb(148, 103, 189),
+    Color32::from_rgb(140, 86, 75),
+    Color32::from_rgb(227, 119, 194),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(188, 189, 34),
+    Color32::from_rgb(23, 190, 207),
+];
+
+/// Kind of chart drawn for the current data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChartKind {
+    #[default]
+    Line,
+    Bar,
+    Scatter,
+    Area,
+}
+
+impl ChartKind {
+    /// Every chart kind, in the order shown to the user.
+    pub const ALL: [ChartKind; 4] = [
+        ChartKind::Line,
+        ChartKind::Bar,
+        ChartKind::Scatter,
+        ChartKind::Area,
+    ];
+
+    /// Name displayed in the UI.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChartKind::Line => "Line",
+            ChartKind::Bar => "Bar",
+            ChartKind::Scatter => "Scatter",
+            ChartKind::Area => "Area",
+        }
+    }
+}
+
+/// Columns and chart kind chosen by the user.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChartConfig {
+    /// Kind of chart.
+    pub kind: ChartKind,
+    /// Column on the horizontal axis.
+    pub x: Option<String>,
+    /// Numeric columns on the vertical axis.
+    pub y: Vec<String>,
+    /// Optional column splitting each Y column into one series per distinct value.
+    pub series_by: Option<String>,
+}
+
+/// Points of one series, in chart coordinates and sorted by x.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartSeries {
+    /// Name shown in the legend.
+    pub name: String,
+    /// (x, y) pairs.
+    pub points: Vec<[f64; 2]>,
+}
+
+/// Data of a chart, extracted from a DataFrame according to a `ChartConfig`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChartData {
+    /// Data type of the X column, used to format the axis.
+    pub x_dtype: DataType,
+    /// Labels of a categorical (non-numeric) X column; points use their positions.
+    pub x_categories: Option<Vec<String>>,
+    /// Series to draw.
+    pub series: Vec<ChartSeries>,
+}
+
+impl ChartData {
+    /// Extracts the chart data in the background.
+    pub async fn compute(df: Arc<DataFrame>, config: ChartConfig) -> Result<Self, String> {
+        Self::from_dataframe(&df, &config).map_err(|e| format!("Chart error: {}", e))
+    }
+
+    /// Extracts one series per Y column (and per `series_by` value), skipping null points.
+    pub fn from_dataframe(df: &DataFrame, config: &ChartConfig) -> PolarsResult<Self> {
+        let Some(x_name) = config.x.as_deref() else {
+            polars_bail!(ComputeError: "choose the X column");
+        };
+        polars_ensure!(!config.y.is_empty(), ComputeError: "choose at least one Y column");
+
+        let x_column = df.column(x_name)?;
+        let x_dtype = x_column.dtype().clone();
+
+        // Numbers and dates are placed by value, anything else by order of appearance.
+        let (x_values, x_categories): (Vec<Option<f64>>, _) = if is_continuous(&x_dtype) {
+            let physical = x_column.to_physical_repr().cast(&DataType::Float64)?;
+            (physical.f64()?.iter().collect(), None)
+        } else {
+            let strings = x_column.cast(&DataType::String)?;
+            let mut categories: Vec<String> = Vec::new();
+            let mut positions: HashMap<String, usize> = HashMap::new();
+            let values = strings
+                .str()?
+                .iter()
+                .map(|value| {
+                    let value = value?.to_string();
+                    let position = *positions.entry(value.clone()).or_insert_with(|| {
+                        categories.push(value);
+                        categories.len() - 1
+                    });
+                    Some(position as f64)
+                })
+                .collect();
+            (values, Some(categories))
+        };
+
+        let keys: Option<Vec<Option<String>>> = match config.series_by.as_deref() {
+            Some(name) => {
+                let strings = df.column(name)?.cast(&DataType::String)?;
+                Some(strings.str()?.iter().map(|k| k.map(String::from)).collect())
+            }
+            None => None,
+        };
+
+        let mut series: Vec<ChartSeries> = Vec::new();
+        let mut series_index: HashMap<String, usize> = HashMap::new();
+
+        for y_name in &config.y {
+            let y_column = df.column(y_name)?.cast(&DataType::Float64)?;
+            for (row, (x, y)) in x_values.iter().zip(y_column.f64()?.iter()).enumerate() {
+                let (Some(x), Some(y)) = (x, y) else {
+                    continue; // Null points are not drawn.
+                };
+
+                let name = match &keys {
+                    Some(keys) => {
+                        let key = keys[row].as_deref().unwrap_or("null");
+                        match config.y.len() {
+                            1 => key.to_string(),
+                            _ => format!("{y_name} ({key})"),
+                        }
+                    }
+                    None => y_name.clone(),
+                };
+
+                let index = *series_index.entry(name.clone()).or_insert_with(|| {
+                    series.push(ChartSeries {
+                        name,
+                        points: Vec::new(),
+                    });
+                    series.len() - 1
+                });
+                series[index].points.push([*x, y]);
+            }
+        }
+
+        polars_ensure!(
+            series.len() <= MAX_SERIES,
+            ComputeError: "too many series ({}), the limit is {}", series.len(), MAX_SERIES
+        );
+
+        for series in &mut series {
+            series.points.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        }
+
+        Ok(Self {
+            x_dtype,
+            x_categories,
+            series,
+        })
+    }
+
+    /// Lays out the chart inside `rect`, returning the shapes to draw.
+    pub fn shapes(&self, kind: ChartKind, rect: Rect, text_color: Color32) -> Vec<ChartShape> {
+        let transform = self.transform(kind, rect);
+        let plot = transform.plot;
+        let mut shapes = Vec::new();
+
+        // Frame of the plot area.
+        let corners = [
+            plot.left_top(),
+            plot.right_top(),
+            plot.right_bottom(),
+            plot.left_bottom(),
+            plot.left_top(),
+        ];
+        shapes.push(ChartShape::Line {
+            points: corners.to_vec(),
+            color: Color32::GRAY,
+            width: 1.0,
+        });
+
+        self.axis_labels(&transform, text_color, &mut shapes);
+
+        let baseline = transform.y.0.max(0.0).min(transform.y.1);
+        let slot = self.bar_slot(&transform);
+
+        for (index, series) in self.series.iter().enumerate() {
+            let color = PALETTE[index % PALETTE.len()];
+            let points: Vec<Pos2> = series
+                .points
+                .iter()
+                .map(|[x, y]| transform.pos(*x, *y))
+                .collect();
+
+            match kind {
+                ChartKind::Line => shapes.push(ChartShape::Line {
+                    points,
+                    color,
+                    width: 2.0,
+                }),
+                ChartKind::Scatter => {
+                    shapes.extend(points.into_iter().map(|center| ChartShape::Circle {
+                        center,
+                        radius: 3.0,
+                        color,
+                    }))
+                }
+                ChartKind::Area => {
+                    let base = transform.pos(0.0, baseline).y;
+                    let fill = color.gamma_multiply(0.4);
+                    // Filled segment by segment: convex, unlike the whole area under the line.
+                    for pair in points.windows(2) {
+                        shapes.extend(area_polygons(pair[0], pair[1], base).into_iter().map(
+                            |points| ChartShape::Polygon {
+                                points,
+                                color: fill,
+                            },
+                        ));
+                    }
+                    shapes.push(ChartShape::Line {
+                        points,
+                        color,
+                        width: 2.0,
+                    });
+                }
+                ChartKind::Bar => {
+                    // Bars of the same x are placed side by side.
+                    let width = slot / self.series.len() as f32;
+                    let base = transform.pos(0.0, baseline).y;
+                    for point in points {
+                        let left = point.x - slot / 2.0 + index as f32 * width;
+                        let (top, bottom) = (point.y.min(base), point.y.max(base));
+                        shapes.push(ChartShape::Rect {
+                            rect: Rect::from_min_max(
+                                Pos2::new(left, top),
+                                Pos2::new(left + width, bottom),
+                            ),
+                            color,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.legend(rect, text_color, &mut shapes);
+
+        shapes
+    }
+
+    /// Finds the point closest to `pointer`, within a few pixels.
+    pub fn hovered_point(&self, kind: ChartKind, rect: Rect, pointer: Pos2) -> Option<String> {
+        let transform = self.transform(kind, rect);
+
+        self.series
+            .iter()
+            .flat_map(|series| series.points.iter().map(move |point| (series, point)))
+            .map(|(series, [x, y])| (transform.pos(*x, *y).distance(pointer), series, x, y))
+            .filter(|(distance, ..)| *distance < 8.0)
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, series, x, y)| {
+                format!(
+                    "{}\n{}: {}",
+                    series.name,
+                    self.format_x(*x),
+                    format_number(*y)
+                )
+            })
+    }
+
+    /// Formats a position of the horizontal axis.
+    fn format_x(&self, x: f64) -> String {
+        match &self.x_categories {
+            Some(categories) => categories
+                .get(x.round() as usize)
+                .cloned()
+                .unwrap_or_default(),
+            None => format_axis_value(x, &self.x_dtype),
+        }
+    }
+
+    /// Computes the plot area and the ranges of both axes.
+    fn transform(&self, kind: ChartKind, rect: Rect) -> Transform {
+        let points = || self.series.iter().flat_map(|series| series.points.iter());
+
+        let (mut x_min, mut x_max) = points().fold((f64::MAX, f64::MIN), |(min, max), p| {
+            (min.min(p[0]), max.max(p[0]))
+        });
+        let (mut y_min, mut y_max) = points().fold((f64::MAX, f64::MIN), |(min, max), p| {
+            (min.min(p[1]), max.max(p[1]))
+        });
+
+        if x_min > x_max {
+            (x_min, x_max, y_min, y_max) = (0.0, 1.0, 0.0, 1.0); // No points at all.
+        }
+
+        // Bars and areas grow from zero.
+        if matches!(kind, ChartKind::Bar | ChartKind::Area) {
+            y_min = y_min.min(0.0);
+            y_max = y_max.max(0.0);
+        }
+
+        // Categories and bars need half a slot of margin on each side.
+        if self.x_categories.is_some() || kind == ChartKind::Bar {
+            let gap = self.min_x_gap().unwrap_or(1.0);
+            x_min -= gap / 2.0;
+            x_max += gap / 2.0;
+        }
+
+        if x_max - x_min <= f64::EPSILON {
+            (x_min, x_max) = (x_min - 0.5, x_max + 0.5);
+        }
+        if y_max - y_min <= f64::EPSILON {
+            (y_min, y_max) = (y_min - 0.5, y_max + 0.5);
+        }
+
+        // Leave room for the y labels, the x labels and the legend.
+        let legend_height = if self.series.len() > 1 { 20.0 } else { 0.0 };
+        let plot = Rect::from_min_max(
+            rect.min + Vec2::new(70.0, 10.0 + legend_height),
+            rect.max - Vec2::new(15.0, 25.0),
+        );
+
+        Transform {
+            plot,
+            x: (x_min, x_max),
+            y: (y_min, y_max),
+        }
+    }
+
+    /// Smallest distance between two distinct x values.
+    fn min_x_gap(&self) -> Option<f64> {
+        let mut xs: Vec<f64> = self
+            .series
+            .iter()
+            .flat_map(|series| series.points.iter().map(|p| p[0]))
+            .collect();
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
+
+        xs.windows(2).map(|pair| pair[1] - pair[0]).reduce(f64::min)
+    }
+
+    /// Width, in points, available to the bars of a single x value.
+    fn bar_slot(&self, transform: &Transform) -> f32 {
+        let gap = self.min_x_gap().unwrap_or(1.0);
+        let width = transform.pos(gap, 0.0).x - transform.pos(0.0, 0.0).x;
+        (width * 0.8).max(1.0)
+    }
+
+    /// Adds tick labels to both axes.
+    fn axis_labels(&self, transform: &Transform, color: Color32, shapes: &mut Vec<ChartShape>) {
+        let plot = transform.plot;
+        let ticks = 5;
+
+        for i in 0..=ticks {
+            let y = transform.y.0 + (transform.y.1 - transform.y.0) * i as f64 / ticks as f64;
+            shapes.push(ChartShape::Text {
+                pos: Pos2::new(plot.left() - 5.0, transform.pos(0.0, y).y),
+                anchor: Align2::RIGHT_CENTER,
+                text: format_number(y),
+                color,
+            });
+        }
+
+        let x_ticks: Vec<f64> = match &self.x_categories {
+            Some(categories) => {
+                // Label at most a dozen categories, evenly spaced.
+                let step = categories.len().div_ceil(12).max(1);
+                (0..categories.len())
+                    .step_by(step)
+                    .map(|i| i as f64)
+                    .collect()
+            }
+            None => (0..=ticks)
+                .map(|i| transform.x.0 + (transform.x.1 - transform.x.0) * i as f64 / ticks as f64)
+                .collect(),
+        };
+
+        for x in x_ticks {
+            shapes.push(ChartShape::Text {
+                pos: Pos2::new(transform.pos(x, 0.0).x, plot.bottom() + 5.0),
+                anchor: Align2::CENTER_TOP,
+                text: self.format_x(x),
+                color,
+            });
+        }
+    }
+
+    /// Adds a legend above the plot area when there is more than one series.
+    fn legend(&self, rect: Rect, color: Color32, shapes: &mut Vec<ChartShape>) {
+        if self.series.len() < 2 {
+            return;
+        }
+
+        let mut x = rect.left() + 70.0;
+        for (index, series) in self.series.iter().enumerate() {
+            let square = Rect::from_min_size(Pos2::new(x, rect.top() + 6.0), Vec2::splat(10.0));
+            shapes.push(ChartShape::Rect {
+                rect: square,
+                color: PALETTE[index % PALETTE.len()],
+            });
+            shapes.push(ChartShape::Text {
+                pos: Pos2::new(square.right() + 4.0, square.center().y),
+                anchor: Align2::LEFT_CENTER,
+                text: series.name.clone(),
+                color,
+            });
+            // Approximate width of the label, good enough for a proportional font.
+            x += 30.0 + 7.0 * series.name.chars().count() as f32;
+        }
+    }
+}
+
+/// Returns true for columns placed on the axis by value rather than by order of appearance.
+fn is_continuous(dtype: &DataType) -> bool {
+    dtype.is_primitive_numeric() || dtype.is_decimal() || dtype.is_temporal()
+}
+
+/// Convex polygons filling the area between a segment of the line and the baseline `base`.
+///
+/// A segment crossing the baseline is split where it crosses it: one triangle on each side.
+fn area_polygons(a: Pos2, b: Pos2, base: f32) -> Vec<Vec<Pos2>> {
+    let (above_a, above_b) = (a.y - base, b.y - base);
+    if above_a * above_b < 0.0 {
+        let t = above_a / (above_a - above_b);
+        let cross = Pos2::new(a.x + t * (b.x - a.x), base);
+        return vec![
+            vec![a, cross, Pos2::new(a.x, base)],
+            vec![cross, b, Pos2::new(b.x, base)],
+        ];
+    }
+    vec![vec![a, b, Pos2::new(b.x, base), Pos2::new(a.x, base)]]
+}
+
+/// Formats a value of the vertical axis.
+fn format_number(value: f64) -> String {
+    match value.abs() {
+        v if v >= 1e6 => format!("{value:.3e}"),
+        v if v.fract() == 0.0 => format!("{value:.0}"),
+        _ => format!("{value:.2}"),
+    }
+}
+
+/// Maps chart coordinates to positions inside the plot area.
+struct Transform {
+    plot: Rect,
+    x: (f64, f64),
+    y: (f64, f64),
+}
+
+impl Transform {
+    fn pos(&self, x: f64, y: f64) -> Pos2 {
+        let fx = ((x - self.x.0) / (self.x.1 - self.x.0)) as f32;
+        let fy = ((y - self.y.0) / (self.y.1 - self.y.0)) as f32;
+        Pos2::new(
+            self.plot.left() + fx * self.plot.width(),
+            self.plot.bottom() - fy * self.plot.height(),
+        )
+    }
+}
+
+/// Drawing primitive of a chart, painted with egui or exported as SVG.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChartShape {
+    Line {
+        points: Vec<Pos2>,
+        color: Color32,
+        width: f32,
+    },
+    Polygon {
+        points: Vec<Pos2>,
+        color: Color32,
+    },
+    Rect {
+        rect: Rect,
+        color: Color32,
+    },
+    Circle {
+        center: Pos2,
+        radius: f32,
+        color: Color32,
+    },
+    Text {
+        pos: Pos2,
+        anchor: Align2,
+        text: String,
+        color: Color32,
+    },
+}
+
+/// Paints the chart shapes with egui.
+pub fn paint_shapes(ui: &Ui, shapes: Vec<ChartShape>) {
+    let painter = ui.painter();
+
+    for shape in shapes {
+        match shape {
+            ChartShape::Line {
+                points,
+                color,
+                width,
+            } => {
+                painter.add(Shape::line(points, Stroke::new(width, color)));
+            }
+            ChartShape::Polygon { points, color } => {
+                painter.add(Shape::convex_polygon(points, color, Stroke::NONE));
+            }
+            ChartShape::Rect { rect, color } => {
+                painter.rect_filled(rect, 0.0, color);
+            }
+            ChartShape::Circle {
+                center,
+                radius,
+                color,
+            } => {
+                painter.circle_filled(center, radius, color);
+            }
+            ChartShape::Text {
+                pos,
+                anchor,
+                text,
+                color,
+            } => {
+                painter.text(pos, anchor, text, FontId::proportional(12.0), color);
+            }
+        }
+    }
+}
+
+/// Serializes the chart shapes as an SVG document of the given size, on a white background.
+pub fn shapes_to_svg(shapes: &[ChartShape], size: Vec2) -> String {
+    let fill = |color: &Color32| {
+        let [r, g, b, a] = color.to_srgba_unmultiplied();
+        format!(
+            "fill=\"#{r:02x}{g:02x}{b:02x}\" fill-opacity=\"{:.2}\"",
+            a as f32 / 255.0
+        )
+    };
+    let stroke = |color: &Color32| {
+        let [r, g, b, _] = color.to_srgba_unmultiplied();
+        format!("stroke=\"#{r:02x}{g:02x}{b:02x}\"")
+    };
+    let points = |points: &[Pos2]| {
+        points
+            .iter()
+            .map(|p| format!("{:.1},{:.1}", p.x, p.y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n",
+        w = size.x,
+        h = size.y
+    );
+    svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+    for shape in shapes {
+        // Writing to a String never fails.
+        let _ = match shape {
+            ChartShape::Line {
+                points: line,
+                color,
+                width,
+            } => writeln!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{width}\"/>",
+                points(line),
+                stroke(color)
+            ),
+            ChartShape::Polygon {
+                points: polygon,
+                color,
+            } => writeln!(
+                svg,
+                "<polygon points=\"{}\" {}/>",
+                points(polygon),
+                fill(color)
+            ),
+            ChartShape::Rect { rect, color } => writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" {}/>",
+                rect.left(),
+                rect.top(),
+                rect.width(),
+                rect.height(),
+                fill(color)
+            ),
+            ChartShape::Circle {
+                center,
+                radius,
+                color,
+            } => writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{radius}\" {}/>",
+                center.x,
+                center.y,
+                fill(color)
+            ),
+            ChartShape::Text {
+                pos,
+                anchor,
+                text,
+                color,
+            } => {
+                let text_anchor = match anchor.x() {
+                    egui::Align::Min => "start",
+                    egui::Align::Center => "middle",
+                    egui::Align::Max => "end",
+                };
+                let baseline = match anchor.y() {
+                    egui::Align::Min => "hanging",
+                    egui::Align::Center => "middle",
+                    egui::Align::Max => "auto",
+                };
+                writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{text_anchor}\" dominant-baseline=\"{baseline}\" font-family=\"sans-serif\" font-size=\"12\" {}>{}</text>",
+                    pos.x,
+                    pos.y,
+                    fill(color),
+                    escape_xml(text)
+                )
+            }
+        };
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escapes the characters with a special meaning in XML.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Marker sent with the screenshot request of a PNG export.
+struct ChartScreenshot;
+
+/// State of the Chart view: the user's choices and the data extracted from the current table.
+#[derive(Default)]
+pub struct ChartView {
+    /// Columns and chart kind chosen by the user.
+    pub config: ChartConfig,
+    /// Chart data and the configuration it was computed for.
+    data: Option<(ChartConfig, PendingTask<ChartData>)>,
+    /// Screen area of the last painted chart.
+    chart_rect: Option<Rect>,
+    /// Destination of a PNG export waiting for its screenshot.
+    png_path: Option<PathBuf>,
+}
+
+impl ChartView {
+    /// Discards the chart data, which described the previous table.
+    pub fn reset(&mut self) {
+        self.data = None;
+    }
+
+    /// Renders the chart controls and the chart of `table`.
+    ///
+    /// Returns a popover to display when an export fails.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        table: &DataFrameContainer,
+        runtime: &Handle,
+    ) -> Option<Box<dyn Popover>> {
+        let mut popover: Option<Box<dyn Popover>> = None;
+
+        self.render_controls(ui, &table.df);
+
+        ui.horizontal(|ui| {
+            if ui.button("Export PNG").clicked() {
+                match runtime.block_on(save_file_dialog("chart.png")) {
+                    Ok(path) => {
+                        // The chart is captured from the next frame, then cropped.
+                        self.png_path = Some(path);
+                        let user_data = UserData::new(ChartScreenshot);
+                        ui.ctx()
+                            .send_viewport_cmd(ViewportCommand::Screenshot(user_data));
+                    }
+                    Err(message) => popover = Some(Box::new(Error { message })),
+                }
+            }
+
+            if ui.button("Export SVG").clicked() {
+                if let Err(message) = self.export_svg(runtime) {
+                    popover = Some(Box::new(Error { message }));
+                }
+            }
+        });
+        ui.separator();
+
+        if let Err(message) = self.save_screenshot(ui.ctx()) {
+            popover = Some(Box::new(Error { message }));
+        }
+
+        // Recompute the chart data whenever the configuration changes.
+        let up_to_date = matches!(&self.data, Some((config, _)) if *config == self.config);
+        if !up_to_date {
+            let future = ChartData::compute(table.df.clone(), self.config.clone());
+            let task = PendingTask::spawn(runtime, ui.ctx(), future);
+            self.data = Some((self.config.clone(), task));
+        }
+
+        let kind = self.config.kind;
+        match self.data.as_mut().and_then(|(_, task)| task.poll()) {
+            None => {
+                ui.centered_and_justified(|ui| {
+                    ui.spinner(); // The chart data is still being extracted.
+                });
+            }
+            Some(Err(message)) => {
+                ui.label(message.as_str());
+            }
+            Some(Ok(data)) => {
+                let size = Vec2::new(ui.available_width(), ui.available_height().max(300.0));
+                let (response, _painter) = ui.allocate_painter(size, Sense::hover());
+                let rect = response.rect;
+
+                paint_shapes(ui, data.shapes(kind, rect, ui.visuals().text_color()));
+                self.chart_rect = Some(rect);
+
+                if let Some(text) = response
+                    .hover_pos()
+                    .and_then(|pointer| data.hovered_point(kind, rect, pointer))
+                {
+                    response.on_hover_text_at_pointer(text);
+                }
+            }
+        }
+
+        popover
+    }
+
+    /// Renders the chart kind and column selectors.
+    fn render_controls(&mut self, ui: &mut Ui, df: &DataFrame) {
+        let schema = df.schema();
+        let columns: Vec<&str> = schema.iter_names().map(|name| name.as_str()).collect();
+        let numeric: Vec<&str> = schema
+            .iter()
+            .filter(|(_, dtype)| dtype.is_primitive_numeric() || dtype.is_decimal())
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        // Forget the columns that are not part of the current data.
+        self.config.x = self
+            .config
+            .x
+            .take()
+            .filter(|x| columns.contains(&x.as_str()));
+        self.config.y.retain(|y| numeric.contains(&y.as_str()));
+        self.config.series_by =
+            (self.config.series_by.take()).filter(|s| columns.contains(&s.as_str()));
+
+        ui.horizontal_wrapped(|ui| {
+            ComboBox::from_label("Type")
+                .selected_text(self.config.kind.name())
+                .show_ui(ui, |ui| {
+                    for kind in ChartKind::ALL {
+                        ui.selectable_value(&mut self.config.kind, kind, kind.name());
+                    }
+                });
+
+            ComboBox::from_label("X")
+                .selected_text(self.config.x.as_deref().unwrap_or("-"))
+                .show_ui(ui, |ui| {
+                    for column in &columns {
+                        ui.selectable_value(&mut self.config.x, Some(column.to_string()), *column);
+                    }
+                });
+
+            ui.menu_button(format!("Y ({})", self.config.y.len()), |ui| {
+                for column in &numeric {
+                    let mut checked = self.config.y.iter().any(|y| y == column);
+                    if ui.checkbox(&mut checked, *column).changed() {
+                        match checked {
+                            true => self.config.y.push(column.to_string()),
+                            false => self.config.y.retain(|y| y != column),
+                        }
+                    }
+                }
+            });
+
+            ComboBox::from_label("Series by")
+                .selected_text(self.config.series_by.as_deref().unwrap_or("-"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.config.series_by, None, "-");
+                    for column in &columns {
+                        ui.selectable_value(
+                            &mut self.config.series_by,
+                            Some(column.to_string()),
+                            *column,
+                        );
+                    }
+                });
+        });
+    }
+
+    /// Writes the current chart as an SVG file chosen by the user.
+    fn export_svg(&mut self, runtime: &Handle) -> Result<(), String> {
+        let Some((_, task)) = self.data.as_mut() else {
+            return Err("There is no chart to export.".to_string());
+        };
+        let Some(Ok(data)) = task.poll() else {
+            return Err("There is no chart to export.".to_string());
+        };
+
+        let size = self
+            .chart_rect
+            .map_or(Vec2::new(800.0, 500.0), |r| r.size());
+        let rect = Rect::from_min_size(Pos2::ZERO, size);
+        let shapes = data.shapes(self.config.kind, rect, Color32::BLACK);
+        let svg = shapes_to_svg(&shapes, size);
+
+        let path = runtime.block_on(save_file_dialog("chart.svg"))?;
+        std::fs::write(path, svg).map_err(|e| format!("Error writing SVG: {}", e))
+    }
+
+    /// Saves the PNG export once the requested screenshot has arrived.
+    fn save_screenshot(&mut self, ctx: &Context) -> Result<(), String> {
+        if self.png_path.is_none() {
+            return Ok(());
+        }
+
+        let image = ctx.input(|i| {
+            i.raw.events.iter().find_map(|event| match event {
+                Event::Screenshot {
+                    user_data, image, ..
+                } if user_data
+                    .data
+                    .as_ref()
+                    .is_some_and(|data| data.downcast_ref::<ChartScreenshot>().is_some()) =>
+                {
+                    Some(image.clone())
+                }
+                _ => None,
+            })
+        });
+
+        let (Some(image), Some(rect)) = (image, self.chart_rect) else {
+            return Ok(()); // Still waiting for the screenshot.
+        };
+        let Some(path) = self.png_path.take() else {
+            return Ok(());
+        };
+
+        let region = image.region(&rect, Some(ctx.pixels_per_point()));
+        let pixels: Vec<u8> = region.pixels.iter().flat_map(|p| p.to_array()).collect();
+
+        image::save_buffer(
+            path,
+            &pixels,
+            region.width() as u32,
+            region.height() as u32,
+            image::ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| format!("Error writing PNG: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(x: &str, y: &[&str], series_by: Option<&str>) -> ChartConfig {
+        ChartConfig {
+            kind: ChartKind::Line,
+            x: Some(x.to_string()),
+            y: y.iter().map(|y| y.to_string()).collect(),
+            series_by: series_by.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_series_by_splits_and_sorts_points() -> PolarsResult<()> {
+        let df = df![
+            "year" => [2021, 2020, 2020, 2021],
+            "kind" => ["Saída", "Saída", "Entrada", "Entrada"],
+            "value" => [Some(2.0), Some(1.0), Some(3.0), None],
+        ]?;
+        let data = ChartData::from_dataframe(&df, &config("year", &["value"], Some("kind")))?;
+
+        assert!(data.x_categories.is_none());
+        assert_eq!(
+            data.series,
+            vec![
+                ChartSeries {
+                    name: "Saída".to_string(),
+                    points: vec![[2020.0, 1.0], [2021.0, 2.0]],
+                },
+                ChartSeries {
+                    name: "Entrada".to_string(),
+                    points: vec![[2020.0, 3.0]],
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_x_uses_categories() -> PolarsResult<()> {
+        let df = df![
+            "kind" => ["b", "a", "b"],
+            "pis" => [1, 2, 3],
+            "cofins" => [4, 5, 6],
+        ]?;
+        let data = ChartData::from_dataframe(&df, &config("kind", &["pis", "cofins"], None))?;
+
+        assert_eq!(
+            data.x_categories,
+            Some(vec!["b".to_string(), "a".to_string()])
+        );
+        assert_eq!(data.series.len(), 2);
+        assert_eq!(data.series[1].name, "cofins");
+        assert_eq!(
+            data.series[1].points,
+            vec![[0.0, 4.0], [0.0, 6.0], [1.0, 5.0]]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_y_is_an_error() -> PolarsResult<()> {
+        let df = df!["x" => [1, 2]]?;
+        assert!(ChartData::from_dataframe(&df, &config("x", &[], None)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_svg_export() -> PolarsResult<()> {
+        let df = df!["x" => [1, 2, 3], "y" => [1.0, 4.0, 9.0]]?;
+        let data = ChartData::from_dataframe(&df, &config("x", &["y"], None))?;
+
+        let size = Vec2::new(400.0, 300.0);
+        let rect = Rect::from_min_size(Pos2::ZERO, size);
+        for kind in ChartKind::ALL {
+            let svg = shapes_to_svg(&data.shapes(kind, rect, Color32::BLACK), size);
+            assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+            assert!(svg.trim_end().ends_with("</svg>"));
+        }
+
+        let svg = shapes_to_svg(&data.shapes(ChartKind::Scatter, rect, Color32::BLACK), size);
+        assert_eq!(svg.matches("<circle").count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_area_split_at_baseline() {
+        let (a, b) = (Pos2::new(0.0, 10.0), Pos2::new(4.0, 30.0));
+        assert_eq!(area_polygons(a, b, 40.0).len(), 1); // Both above the baseline.
+
+        let polygons = area_polygons(a, b, 20.0);
+        let cross = Pos2::new(2.0, 20.0);
+        assert_eq!(
+            polygons,
+            [
+                vec![a, cross, Pos2::new(0.0, 20.0)],
+                vec![cross, b, Pos2::new(4.0, 20.0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a < b & \"c\""), "a &lt; b &amp; &quot;c&quot;");
+    }
+}
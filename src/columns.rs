@@ -0,0 +1,195 @@
+use egui::{Id, Sense, Ui};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Order, visibility and pinning of the columns of a table, saved per file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    /// Column names in the order chosen by the user.
+    pub order: Vec<String>,
+    /// Columns not shown, nor exported.
+    pub hidden: Vec<String>,
+    /// Columns kept on the left while scrolling horizontally.
+    pub pinned: Vec<String>,
+}
+
+impl ColumnLayout {
+    /// Column names of `df` in display order.
+    ///
+    /// Pinned columns come first, then the saved order; columns unknown to the layout
+    /// (new in this result) come last, in their original order.
+    pub fn ordered(&self, df: &DataFrame) -> Vec<String> {
+        let names: Vec<String> = df
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        let mut ordered: Vec<String> = Vec::with_capacity(names.len());
+        let candidates = self.pinned.iter().chain(&self.order).chain(&names);
+        for name in candidates {
+            if names.contains(name) && !ordered.contains(name) {
+                ordered.push(name.clone());
+            }
+        }
+
+        ordered
+    }
+
+    /// Names of the visible columns, in display order.
+    pub fn visible(&self, df: &DataFrame) -> Vec<String> {
+        let mut visible = self.ordered(df);
+        visible.retain(|name| !self.hidden.contains(name));
+        visible
+    }
+
+    /// Number of visible pinned columns, which come first in display order.
+    pub fn pinned_count(&self, df: &DataFrame) -> usize {
+        let visible = self.visible(df);
+        visible
+            .iter()
+            .take_while(|name| self.pinned.contains(name))
+            .count()
+    }
+
+    /// Selects the visible columns of `df`, in display order.
+    pub fn apply(&self, df: &DataFrame) -> PolarsResult<DataFrame> {
+        df.select(self.visible(df))
+    }
+
+    /// Hides a column.
+    pub fn hide(&mut self, name: &str) {
+        if !self.hidden.iter().any(|hidden| hidden == name) {
+            self.hidden.push(name.to_string());
+        }
+    }
+
+    /// Pins a column to the left, or unpins it.
+    pub fn toggle_pin(&mut self, name: &str) {
+        match self.pinned.iter().position(|pinned| pinned == name) {
+            Some(index) => {
+                self.pinned.remove(index);
+            }
+            None => self.pinned.push(name.to_string()),
+        }
+    }
+
+    /// Moves the column at position `from` (in display order) to position `to`.
+    pub fn move_column(&mut self, df: &DataFrame, from: usize, to: usize) {
+        let mut order = self.ordered(df);
+        if from >= order.len() || to >= order.len() {
+            return;
+        }
+
+        let name = order.remove(from);
+        order.insert(to, name);
+
+        // Pinned columns keep the relative order they were given.
+        self.pinned
+            .sort_by_key(|pinned| order.iter().position(|name| name == pinned));
+        self.order = order;
+    }
+
+    /// Renders the column manager: drag to reorder, visibility checkboxes and pin buttons.
+    ///
+    /// Returns true when the layout changed.
+    pub fn render_manager(&mut self, ui: &mut Ui, df: &DataFrame) -> bool {
+        let before = self.clone();
+        let mut moved: Option<(usize, usize)> = None;
+
+        ui.label("Drag the names to reorder the columns.");
+        ui.separator();
+
+        for (index, name) in self.ordered(df).iter().enumerate() {
+            ui.horizontal(|ui| {
+                let mut visible = !self.hidden.contains(name);
+                if ui
+                    .checkbox(&mut visible, "")
+                    .on_hover_text("Visible")
+                    .changed()
+                {
+                    match visible {
+                        true => self.hidden.retain(|hidden| hidden != name),
+                        false => self.hide(name),
+                    }
+                }
+
+                let pinned = self.pinned.contains(name);
+                if ui
+                    .selectable_label(pinned, "📌")
+                    .on_hover_text("Pin to the left")
+                    .clicked()
+                {
+                    self.toggle_pin(name);
+                }
+
+                let id = Id::new(("column_manager", name));
+                let response = ui
+                    .dnd_drag_source(id, index, |ui| {
+                        ui.add(egui::Label::new(format!("☰ {name}")).sense(Sense::hover()));
+                    })
+                    .response;
+
+                // Dropping a column on another one moves it there.
+                if let Some(from) = response.dnd_release_payload::<usize>() {
+                    moved = Some((*from, index));
+                }
+            });
+        }
+
+        if let Some((from, to)) = moved {
+            self.move_column(df, from, to);
+        }
+
+        ui.separator();
+        if ui.button("Reset").clicked() {
+            *self = ColumnLayout::default();
+        }
+
+        *self != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PolarsResult<DataFrame> {
+        df!["a" => [1], "b" => [2], "c" => [3], "d" => [4]]
+    }
+
+    #[test]
+    fn test_ordered_visible_and_pinned() -> PolarsResult<()> {
+        let df = sample()?;
+        let layout = ColumnLayout {
+            order: vec!["c".to_string(), "x".to_string(), "a".to_string()],
+            hidden: vec!["a".to_string()],
+            pinned: vec!["d".to_string()],
+        };
+
+        assert_eq!(layout.ordered(&df), ["d", "c", "a", "b"]);
+        assert_eq!(layout.visible(&df), ["d", "c", "b"]);
+        assert_eq!(layout.pinned_count(&df), 1);
+
+        let applied = layout.apply(&df)?;
+        assert_eq!(applied.get_column_names(), ["d", "c", "b"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_and_pin() -> PolarsResult<()> {
+        let df = sample()?;
+        let mut layout = ColumnLayout::default();
+
+        layout.move_column(&df, 3, 0);
+        assert_eq!(layout.ordered(&df), ["d", "a", "b", "c"]);
+
+        layout.toggle_pin("c");
+        assert_eq!(layout.ordered(&df), ["c", "d", "a", "b"]);
+        layout.toggle_pin("c");
+        assert_eq!(layout.ordered(&df), ["d", "a", "b", "c"]);
+
+        Ok(())
+    }
+}
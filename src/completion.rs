@@ -0,0 +1,282 @@
+use crate::{SQL_KEYWORDS, TokenKind, tokenize};
+
+use egui::Rect;
+
+/// Functions of Polars SQL offered by the autocompletion, as registered by polars-sql
+/// (`EXTRACT` and `TRIM` are special forms of its parser).
+pub const SQL_FUNCTIONS: &[&str] = &[
+    "ABS",
+    "ARRAY_AGG",
+    "AVG",
+    "CBRT",
+    "CEIL",
+    "COALESCE",
+    "CONCAT",
+    "CONCAT_WS",
+    "COS",
+    "COUNT",
+    "DATE",
+    "DATE_PART",
+    "DIV",
+    "ENDS_WITH",
+    "EXP",
+    "EXTRACT",
+    "FLOOR",
+    "GREATEST",
+    "IF",
+    "IFNULL",
+    "INITCAP",
+    "LEAST",
+    "LENGTH",
+    "LN",
+    "LOG10",
+    "LOWER",
+    "LTRIM",
+    "MAX",
+    "MEDIAN",
+    "MIN",
+    "MOD",
+    "NULLIF",
+    "OCTET_LENGTH",
+    "POWER",
+    "QUANTILE_CONT",
+    "REGEXP_LIKE",
+    "REPLACE",
+    "REVERSE",
+    "ROUND",
+    "RTRIM",
+    "SIGN",
+    "SQRT",
+    "STARTS_WITH",
+    "STDDEV",
+    "STRFTIME",
+    "STRPOS",
+    "STRPTIME",
+    "SUBSTR",
+    "SUM",
+    "TIMESTAMP",
+    "TRIM",
+    "UPPER",
+    "VARIANCE",
+];
+
+/// Maximum number of suggestions shown.
+const MAX_SUGGESTIONS: usize = 12;
+
+/// What a completion stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Table,
+    Column,
+    Keyword,
+    Function,
+}
+
+impl CompletionKind {
+    /// Name shown next to the suggestion.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompletionKind::Table => "table",
+            CompletionKind::Column => "column",
+            CompletionKind::Keyword => "keyword",
+            CompletionKind::Function => "function",
+        }
+    }
+}
+
+/// A word the SQL editor can complete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub kind: CompletionKind,
+    pub label: String,
+}
+
+impl Completion {
+    pub fn new(kind: CompletionKind, label: impl ToString) -> Self {
+        Self {
+            kind,
+            label: label.to_string(),
+        }
+    }
+
+    /// Completions of the SQL keywords and functions.
+    pub fn sql() -> Vec<Self> {
+        let keywords = SQL_KEYWORDS
+            .iter()
+            .map(|keyword| Completion::new(CompletionKind::Keyword, keyword));
+        let functions = SQL_FUNCTIONS
+            .iter()
+            .map(|function| Completion::new(CompletionKind::Function, function));
+
+        keywords.chain(functions).collect()
+    }
+
+    /// Text inserted in the query: columns are quoted with backticks, functions opened.
+    pub fn insert_text(&self) -> String {
+        let plain = self
+            .label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        match self.kind {
+            CompletionKind::Column => format!("`{}`", self.label),
+            CompletionKind::Table if !plain => format!("`{}`", self.label),
+            CompletionKind::Function => format!("{}(", self.label),
+            _ => self.label.clone(),
+        }
+    }
+}
+
+/// Lowercase text without accents, so that "calculo" finds "Cálculo".
+pub fn fold(text: &str) -> String {
+    text.chars()
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            'ñ' => 'n',
+            c => c,
+        })
+        .collect()
+}
+
+/// Word being typed before the cursor (a byte offset): its start and its text.
+///
+/// A name opened with a backtick is a word up to the cursor, spaces included.
+pub fn word_before(text: &str, cursor: usize) -> Option<(usize, &str)> {
+    let tokens = tokenize(text.get(..cursor)?);
+    let last = tokens.last()?;
+    let word = &text[last.range.clone()];
+
+    match last.kind {
+        TokenKind::Identifier | TokenKind::Keyword | TokenKind::Function => {
+            Some((last.range.start, word))
+        }
+        TokenKind::QuotedIdentifier
+            if word.starts_with('`') && (word.len() == 1 || !word.ends_with('`')) =>
+        {
+            Some((last.range.start, word))
+        }
+        _ => None,
+    }
+}
+
+/// Suggestions for the word being typed, best first: names starting with it, then names
+/// containing it, ignoring case and accents.
+pub fn suggestions<'a>(word: &str, completions: &'a [Completion]) -> Vec<&'a Completion> {
+    let quoted = word.starts_with('`');
+    let key = fold(word.trim_start_matches('`'));
+    if key.is_empty() && !quoted {
+        return Vec::new();
+    }
+
+    let candidates = completions
+        .iter()
+        .filter(|completion| {
+            !quoted
+                || matches!(
+                    completion.kind,
+                    CompletionKind::Column | CompletionKind::Table
+                )
+        })
+        .filter(|completion| !completion.label.eq_ignore_ascii_case(word)); // Already typed.
+
+    let (mut starting, mut containing) = (Vec::new(), Vec::new());
+    for completion in candidates {
+        let label = fold(&completion.label);
+        if label.starts_with(&key) {
+            starting.push(completion);
+        } else if key.chars().count() >= 2 && label.contains(&key) {
+            containing.push(completion);
+        }
+    }
+
+    starting.extend(containing);
+    starting.truncate(MAX_SUGGESTIONS);
+    starting
+}
+
+/// State of the suggestions list of the SQL editor, kept in the egui memory.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionPopup {
+    /// Start of the word the suggestions are for.
+    pub word_start: usize,
+    /// Suggestion highlighted.
+    pub selected: usize,
+    /// The user moved through the list, so Enter picks a suggestion.
+    pub navigated: bool,
+    /// Escape closed the list for the word starting here.
+    pub dismissed: Option<usize>,
+    /// Where the list was shown on the last frame.
+    pub rect: Option<Rect>,
+}
+
+impl CompletionPopup {
+    /// Follows the word being typed, starting over when it is another word.
+    pub fn track(&mut self, word_start: usize, count: usize) {
+        if word_start != self.word_start {
+            *self = CompletionPopup {
+                word_start,
+                dismissed: self.dismissed.filter(|start| *start == word_start),
+                ..Default::default()
+            };
+        }
+        self.selected = self.selected.min(count.saturating_sub(1));
+    }
+
+    /// Whether the list is shown for the current word.
+    pub fn is_open(&self) -> bool {
+        self.dismissed != Some(self.word_start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_before() {
+        let text = "SELECT `Valor da Ba FROM";
+        assert_eq!(word_before(text, 19), Some((7, "`Valor da Ba")));
+        assert_eq!(word_before(text, 3), Some((0, "SEL")));
+        assert_eq!(word_before("SELECT ", 7), None);
+        assert_eq!(word_before("SELECT `a` ", 10), None); // A closed name.
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let mut completions = vec![
+            Completion::new(CompletionKind::Table, "Notas Fiscais"),
+            Completion::new(
+                CompletionKind::Column,
+                "Valor da Base de Cálculo das Contribuições",
+            ),
+            Completion::new(CompletionKind::Column, "Base de Cálculo"),
+        ];
+        completions.extend(Completion::sql());
+
+        let labels = |word| -> Vec<String> {
+            suggestions(word, &completions)
+                .iter()
+                .map(|completion| completion.insert_text())
+                .collect()
+        };
+
+        // Names starting with the word come first; accents and case are ignored.
+        assert_eq!(
+            labels("`base de calc"),
+            [
+                "`Base de Cálculo`",
+                "`Valor da Base de Cálculo das Contribuições`"
+            ]
+        );
+        assert_eq!(labels("`nota"), ["`Notas Fiscais`"]);
+        assert_eq!(labels("STRF"), ["STRFTIME("]);
+        assert!(labels("SELECT").is_empty()); // Already typed.
+        assert!(labels("").is_empty());
+    }
+}
@@ -1,9 +1,14 @@
 use crate::{
-    ExtraInteractions,
+    CellFormat, ConditionalFormatting, CsvOptions, ExtraInteractions, FormatSettings,
     data::{DataFilters, DataFrameContainer, SortState},
+    format_file_size, get_extension,
+    selection::{CopyFormat, Selection, copy_selection},
 };
 
-use egui::{Color32, Frame, Grid, Layout, Stroke, TextStyle, Ui};
+use egui::{
+    Color32, Event, Frame, Grid, Key, Label, Layout, Modifiers, RichText, ScrollArea, Sense,
+    Stroke, TextEdit, TextStyle, Ui, scroll_area::ScrollBarVisibility,
+};
 use egui_extras::{Column, TableBuilder, TableRow};
 use parquet::{
     basic::ColumnOrder,
@@ -14,16 +19,63 @@ use parquet::{
 };
 use polars::prelude::*;
 use rfd::AsyncFileDialog;
-use std::{fs::File, path::Path};
+use std::{
+    collections::HashSet,
+    fs::File,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+/// Metadata describing the file behind the loaded data, independent of its format.
+#[derive(Debug)]
+pub enum FileMetadata {
+    /// Metadata read from the Parquet footer.
+    Parquet(ParquetMetaData),
+    /// Metadata gathered by scanning a CSV file.
+    Csv(CsvMetadata),
+}
 
-// Struct to hold Parquet file metadata.  This is used for reading Parquet-specific metadata.
-pub struct FileMetadata {
-    info: ParquetMetaData, // Parquet metadata.
+/// Struct to hold CSV file metadata.
+#[derive(Debug)]
+pub struct CsvMetadata {
+    /// File size in bytes.
+    file_size: u64,
+    /// Delimiter used to read the file.
+    delimiter: char,
+    /// How the bytes of the file are decoded (the encoding is not detected).
+    read_as: &'static str,
+    /// Number of data rows (header excluded).
+    num_rows: usize,
+    /// Polars schema inferred from the first rows of the file.
+    schema: SchemaRef,
 }
 
 impl FileMetadata {
     /// Creates a `FileMetadata` instance from a filename.
-    pub fn from_filename(filename: &str) -> Result<Self, String> {
+    ///
+    /// The CSV delimiter and options are required for CSV files and ignored for other formats;
+    /// `num_rows`, when known, saves counting the rows of a CSV file.
+    pub fn from_filename(
+        filename: &str,
+        csv_delimiter: Option<&str>,
+        csv_options: &CsvOptions,
+        num_rows: Option<usize>,
+    ) -> Result<Self, String> {
+        match get_extension(filename).as_deref() {
+            Some("parquet") => Self::from_parquet(filename),
+            Some("csv") => {
+                let delimiter = match csv_delimiter.map(str::as_bytes) {
+                    Some([delimiter]) => *delimiter,
+                    _ => return Err("The CSV delimiter must be a single character.".to_string()),
+                };
+                Self::from_csv(filename, delimiter, csv_options, num_rows)
+            }
+            _ => Err(format!("Unknown file type: {}", filename)),
+        }
+    }
+
+    /// Reads the metadata stored in the footer of a Parquet file.
+    fn from_parquet(filename: &str) -> Result<Self, String> {
         let path = Path::new(filename);
 
         // Attempt to open the file.
@@ -34,15 +86,51 @@ impl FileMetadata {
             .map_err(|error| format!("Error creating Parquet reader: {}", error))?;
 
         // Extract and store the Parquet metadata.
-        Ok(Self {
-            info: reader.metadata().to_owned(),
-        })
+        Ok(Self::Parquet(reader.metadata().to_owned()))
+    }
+
+    /// Scans a CSV file to infer its schema and count its rows, unless `num_rows` is known.
+    ///
+    /// Counting the rows reads the whole file: run it in a background task.
+    fn from_csv(
+        filename: &str,
+        delimiter: u8,
+        options: &CsvOptions,
+        num_rows: Option<usize>,
+    ) -> Result<Self, String> {
+        let file_size = std::fs::metadata(filename)
+            .map_err(|error| format!("Could not read file metadata: {}", error))?
+            .len();
+
+        let mut lazyframe = DataFrameContainer::scan_csv(filename, delimiter, options)?;
+
+        // The schema is inferred from the first rows, exactly as when loading the data.
+        let schema = lazyframe
+            .collect_schema()
+            .map_err(|error| format!("Error inferring CSV schema: {}", error))?;
+
+        // Polars counts the rows of a CSV file without parsing its values.
+        let num_rows = match num_rows {
+            Some(num_rows) => num_rows,
+            None => lazyframe
+                .select([len()])
+                .collect()
+                .and_then(|df| df[0].get(0).map(|value| value.extract::<usize>()))
+                .map_err(|error| format!("Error counting CSV rows: {}", error))?
+                .unwrap_or_default(),
+        };
+
+        Ok(Self::Csv(CsvMetadata {
+            file_size,
+            delimiter: delimiter as char,
+            read_as: "UTF-8, invalid bytes replaced",
+            num_rows,
+            schema,
+        }))
     }
 
     /// Renders the file metadata in the UI using egui.
     pub fn render_metadata(&self, ui: &mut Ui) {
-        let file_metadata = self.info.file_metadata();
-
         // Use a frame to visually group the metadata.
         Frame::default()
             .stroke(Stroke::new(1.0, Color32::GRAY)) // Thin gray border for visual separation.
@@ -55,37 +143,82 @@ impl FileMetadata {
                     .num_columns(2)
                     .spacing([10.0, 20.0])
                     .striped(true)
-                    .show(ui, |ui| {
-                        let created_by = file_metadata.created_by().unwrap_or("unknown");
+                    .show(ui, |ui| match self {
+                        FileMetadata::Parquet(info) => Self::render_parquet_metadata(info, ui),
+                        FileMetadata::Csv(info) => Self::render_csv_metadata(info, ui),
+                    });
+            });
+    }
 
-                        ui.label("Data processing:");
-                        ui.label(created_by);
-                        ui.end_row();
+    /// Fills the metadata grid with Parquet information.
+    fn render_parquet_metadata(info: &ParquetMetaData, ui: &mut Ui) {
+        let file_metadata = info.file_metadata();
 
-                        let version = env!("CARGO_PKG_VERSION");
+        let created_by = file_metadata.created_by().unwrap_or("unknown");
 
-                        ui.label("Polars View Version");
-                        ui.label(version);
-                        ui.end_row();
+        ui.label("Data processing:");
+        ui.label(created_by);
+        ui.end_row();
 
-                        let nc = file_metadata.schema_descr().num_columns();
+        let version = env!("CARGO_PKG_VERSION");
 
-                        ui.label("Columns:");
-                        ui.label(nc.to_string());
-                        ui.end_row();
+        ui.label("Polars View Version");
+        ui.label(version);
+        ui.end_row();
 
-                        let nr = file_metadata.num_rows();
+        let nc = file_metadata.schema_descr().num_columns();
 
-                        ui.label("Rows:");
-                        ui.label(nr.to_string());
-                        ui.end_row();
-                    });
-            });
+        ui.label("Columns:");
+        ui.label(nc.to_string());
+        ui.end_row();
+
+        let nr = file_metadata.num_rows();
+
+        ui.label("Rows:");
+        ui.label(nr.to_string());
+        ui.end_row();
+    }
+
+    /// Fills the metadata grid with CSV information.
+    fn render_csv_metadata(info: &CsvMetadata, ui: &mut Ui) {
+        let version = env!("CARGO_PKG_VERSION");
+
+        ui.label("Polars View Version");
+        ui.label(version);
+        ui.end_row();
+
+        ui.label("File size:");
+        ui.label(format_file_size(info.file_size));
+        ui.end_row();
+
+        ui.label("Delimiter:");
+        ui.label(format!("{:?}", info.delimiter));
+        ui.end_row();
+
+        ui.label("Read as:");
+        ui.label(info.read_as);
+        ui.end_row();
+
+        ui.label("Columns:");
+        ui.label(info.schema.len().to_string());
+        ui.end_row();
+
+        ui.label("Rows:");
+        ui.label(info.num_rows.to_string());
+        ui.end_row();
     }
 
     /// Renders the file schema information in the UI using egui.
     pub fn render_schema(&self, ui: &mut Ui) {
-        let file_metadata = self.info.file_metadata();
+        match self {
+            FileMetadata::Parquet(info) => Self::render_parquet_schema(info, ui),
+            FileMetadata::Csv(info) => render_polars_schema(&info.schema, ui),
+        }
+    }
+
+    /// Renders the physical types and sort orders of a Parquet schema.
+    fn render_parquet_schema(info: &ParquetMetaData, ui: &mut Ui) {
+        let file_metadata = info.file_metadata();
         // Iterate over the columns in the schema.
         for (idx, field) in file_metadata.schema_descr().columns().iter().enumerate() {
             // Create a collapsing header for each column to show its details.
@@ -114,9 +247,180 @@ impl FileMetadata {
     }
 }
 
+/// Renders a Polars schema as one collapsing header per column.
+pub fn render_polars_schema(schema: &Schema, ui: &mut Ui) {
+    for (idx, (name, dtype)) in schema.iter().enumerate() {
+        ui.collapsing(name.as_str(), |ui| {
+            ui.label(format!("type: {}", dtype));
+            ui.label(format!("position: {}", idx + 1));
+        });
+    }
+}
+
+/// Actions requested by the user while interacting with the table.
+#[derive(Debug, Clone)]
+pub enum TableAction {
+    /// Sort the data with the given filters.
+    Sort(Box<DataFilters>),
+    /// Open the statistics profile of the named column.
+    Profile(String),
+    /// Open the value distribution chart of the named column.
+    Distribution(String),
+    /// Open the filter editor of the named column.
+    Filter(String),
+    /// Hide the named column.
+    HideColumn(String),
+    /// Pin the named column to the left, or unpin it.
+    PinColumn(String),
+    /// Open the column manager.
+    ManageColumns,
+    /// Open the conditional formatting rules of the named column.
+    Conditional(String),
+    /// Show an error, such as a failed copy.
+    Error(String),
+}
+
+/// Interaction state of a rendered table, kept by its view across frames.
+#[derive(Debug, Default)]
+pub struct TableState {
+    /// Cells matching the search, as (row, column) positions.
+    pub matches: HashSet<(usize, usize)>,
+    /// Search match the user navigated to.
+    pub current_match: Option<(usize, usize)>,
+    /// Row to scroll to on the next frame.
+    pub scroll_to_row: Option<usize>,
+    /// Cells selected by the user.
+    pub selection: Option<Selection>,
+    /// Include the column names when copying the selection as TSV.
+    pub copy_headers: bool,
+    /// Show the full value of the selected cell in the cell inspector.
+    pub inspector: bool,
+    /// Number of leading columns pinned to the left.
+    pub pinned: usize,
+    /// Vertical scroll position shared by the pinned and the scrolled columns.
+    pub scroll_offset: f32,
+    /// Offer the column layout (hide, pin, manage) and conditional formatting actions in the header menu.
+    pub manage_columns: bool,
+    /// Show the row numbers, frozen left of the data.
+    pub row_numbers: bool,
+    /// State of the "Go to row" field.
+    pub go_to: GoToRow,
+    /// Rules formatting the values of the cells.
+    pub formats: FormatSettings,
+    /// Rules coloring the cells.
+    pub conditional: ConditionalFormatting,
+}
+
+/// State of the "Go to row" field above the table.
+#[derive(Debug, Default)]
+pub struct GoToRow {
+    /// Row number typed by the user.
+    pub text: String,
+    /// The number is a position in the current view rather than a row of the file.
+    pub view_position: bool,
+    /// Focus the field on the next frame.
+    pub focus: bool,
+    /// Why the last row could not be shown.
+    pub message: Option<String>,
+}
+
+/// Part of the columns rendered by one `TableBuilder`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TablePart {
+    /// Every column, when none is pinned.
+    Whole,
+    /// The pinned columns, on the left.
+    Pinned,
+    /// The other columns, scrolled horizontally.
+    Scrolled,
+}
+
+impl TableState {
+    /// Updates the selection after a click on a cell or a column header.
+    fn update_selection(
+        &mut self,
+        clicked: Option<((usize, usize), Modifiers)>,
+        selected_column: Option<usize>,
+    ) {
+        if let Some(column) = selected_column {
+            self.selection = Some(Selection::column(column));
+        }
+
+        let Some(((row, column), modifiers)) = clicked else {
+            return;
+        };
+
+        self.selection = match (self.selection, modifiers.shift, modifiers.command) {
+            (Some(mut selection), true, _) => {
+                selection.extend(row, column);
+                Some(selection)
+            }
+            (_, _, true) => Some(Selection::row(row, column)),
+            _ => Some(Selection::cell(row, column)),
+        };
+    }
+}
+
 impl DataFrameContainer {
+    /// Renders the Polars schema of the current DataFrame (the result of the last query).
+    pub fn render_schema(&self, ui: &mut Ui) {
+        render_polars_schema(self.df.schema(), ui);
+    }
+
+    /// Renders the row numbers toggle and the "Go to row" field above the table.
+    pub fn render_row_navigation(&self, ui: &mut Ui, state: &mut TableState) {
+        let go_to = &mut state.go_to;
+        let mut position: Option<usize> = None;
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.row_numbers, "Row numbers");
+            ui.separator();
+
+            ui.label("Go to row:");
+            let response = ui.add(
+                TextEdit::singleline(&mut go_to.text)
+                    .hint_text("N (Ctrl+G)")
+                    .desired_width(100.0),
+            );
+            if std::mem::take(&mut go_to.focus) {
+                response.request_focus();
+            }
+
+            // Sorted or filtered data still knows the rows of its file; query results do not.
+            if self.row_numbers.is_some() {
+                ui.selectable_value(&mut go_to.view_position, false, "of the file");
+                ui.selectable_value(&mut go_to.view_position, true, "of the view");
+            }
+
+            let enter = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            if ui.button("Go").clicked() || enter {
+                let row = go_to.text.trim().parse::<usize>();
+                position = row
+                    .ok()
+                    .and_then(|row| self.row_position(row, !go_to.view_position));
+                go_to.message = match position {
+                    Some(_) => None,
+                    None => Some(format!(
+                        "Row {} is not in the current data.",
+                        go_to.text.trim()
+                    )),
+                };
+            }
+
+            if let Some(message) = &go_to.message {
+                ui.label(message.as_str());
+            }
+        });
+
+        // Scroll to the row and select it.
+        if let Some(position) = position {
+            state.scroll_to_row = Some(position);
+            state.selection = Some(Selection::row(position, 0));
+        }
+    }
+
     /// Renders the DataFrame as a table using egui.
-    pub fn render_table(&self, ui: &mut Ui) -> Option<DataFilters> {
+    pub fn render_table(&self, ui: &mut Ui, state: &mut TableState) -> Option<TableAction> {
         let style = ui.style().as_ref();
 
         /// Checks if a given column is currently sorted.
@@ -131,7 +435,13 @@ impl DataFrameContainer {
             }
         }
 
-        let mut filters: Option<DataFilters> = None; // The `DataFilters` to be returned if sorting is applied.
+        let mut action: Option<TableAction> = None; // The action to be returned, if any.
+        let mut clicked: Option<((usize, usize), Modifiers)> = None; // Cell clicked by the user.
+        let mut selected_column: Option<usize> = None; // Column selected from its header.
+        let mut copy: Option<CopyFormat> = None; // Format requested from the cell menu.
+        let mut copy_headers = state.copy_headers;
+        let mut inspector = state.inspector;
+        let manage_columns = state.manage_columns;
         let mut sorted_column = self.filters.sort.clone(); // The current sort state of the table.
 
         let text_height = TextStyle::Body.resolve(style).size; // Height of a text line, used for row height calculation.
@@ -155,110 +465,298 @@ impl DataFrameContainer {
             .resizable(true)
             .clip(true);
 
-        // Defines a closure to render the table header.  This creates the interactive sort buttons.
-        let analyze_header = |mut table_row: TableRow<'_, '_>| {
-            // Iterate over the column names in the DataFrame.
-            for column_name in self.df.get_column_names() {
-                table_row.col(|ui| {
-                    // Determine the current sort state of the column.
-                    let column_label = if is_sorted_column(&sorted_column, column_name) {
-                        sorted_column.clone().unwrap() // Display the sort state (ascending/descending).
-                    } else {
-                        SortState::NotSorted(column_name.to_string()) // Default to "not sorted".
-                    };
-
-                    // Create a centered layout for the sort button.
-                    ui.horizontal_centered(|ui| {
-                        // Creates the sort button using the ExtraInteractions trait.
-                        let response = ui.sort_button(&mut sorted_column, column_label.clone());
-                        if response.clicked() {
-                            // If the sort button is clicked, create a DataFilters to trigger a resort.
-                            filters = Some(DataFilters {
-                                sort: sorted_column.clone(), // Updates the filters with the new sort state.
-                                ..self.filters.clone()       // Inherit other filter settings.
+        let width = self.df.width();
+        let pinned = state.pinned.min(width);
+
+        // Colors of the conditional formatting rules, from their last evaluation.
+        let colors = state.conditional.colors().clone();
+
+        // Formatting of each column, resolved once per frame.
+        let formats: Vec<CellFormat> = self
+            .df
+            .get_columns()
+            .iter()
+            .map(|column| state.formats.resolve(column.name(), column.dtype()))
+            .collect();
+
+        // Row numbers are frozen left of the data: the position in the view, and the row of the file.
+        let file_rows = self
+            .row_numbers
+            .as_ref()
+            .and_then(|numbers| numbers.idx().ok());
+        let number_columns = match (state.row_numbers, file_rows.is_some()) {
+            (false, _) => 0,
+            (true, false) => 1,
+            (true, true) => 2,
+        };
+        let scroll_to_row = state.scroll_to_row.take();
+        let scroll_offset = state.scroll_offset;
+
+        // Renders the given columns as a table, returning its vertical scroll offset.
+        // Pinned columns get a table of their own, left of the horizontally scrolled one.
+        let mut render = |ui: &mut Ui, columns: Range<usize>, part: TablePart| -> f32 {
+            let number_columns = match part {
+                TablePart::Scrolled => 0,
+                TablePart::Whole | TablePart::Pinned => number_columns,
+            };
+
+            // Defines a closure to render the table header.  This creates the interactive sort buttons.
+            let analyze_header = |mut table_row: TableRow<'_, '_>| {
+                if number_columns > 0 {
+                    table_row.col(|ui| {
+                        ui.strong("#").on_hover_text("Position in the current view");
+                    });
+                }
+                if number_columns > 1 {
+                    table_row.col(|ui| {
+                        ui.strong("Row").on_hover_text("Row of the file");
+                    });
+                }
+
+                // Iterate over the column names in the DataFrame.
+                for column_index in columns.clone() {
+                    let column_name = self.df.get_columns()[column_index].name().as_str();
+                    table_row.col(|ui| {
+                        // Determine the current sort state of the column.
+                        let column_label = if is_sorted_column(&sorted_column, column_name) {
+                            sorted_column.clone().unwrap() // Display the sort state (ascending/descending).
+                        } else {
+                            SortState::NotSorted(column_name.to_string()) // Default to "not sorted".
+                        };
+
+                        // Create a centered layout for the sort button.
+                        ui.horizontal_centered(|ui| {
+                            // Creates the sort button using the ExtraInteractions trait.
+                            let response = ui.sort_button(&mut sorted_column, column_label.clone());
+                            if response.clicked() {
+                                // If the sort button is clicked, create a DataFilters to trigger a resort.
+                                action = Some(TableAction::Sort(Box::new(DataFilters {
+                                    sort: sorted_column.clone(), // Updates the filters with the new sort state.
+                                    ..self.filters.clone()       // Inherit other filter settings.
+                                })));
+                            }
+
+                            // Right click on the header opens the column menu.
+                            response.context_menu(|ui| {
+                                if ui.button("Filter...").clicked() {
+                                    action = Some(TableAction::Filter(column_name.to_string()));
+                                    ui.close_menu();
+                                }
+                                if ui.button("Column profile").clicked() {
+                                    action = Some(TableAction::Profile(column_name.to_string()));
+                                    ui.close_menu();
+                                }
+                                if ui.button("Distribution").clicked() {
+                                    action =
+                                        Some(TableAction::Distribution(column_name.to_string()));
+                                    ui.close_menu();
+                                }
+                                if ui.button("Select column").clicked() {
+                                    selected_column = Some(column_index);
+                                    ui.close_menu();
+                                }
+                                if manage_columns {
+                                    ui.separator();
+                                    if ui.button("Hide column").clicked() {
+                                        action =
+                                            Some(TableAction::HideColumn(column_name.to_string()));
+                                        ui.close_menu();
+                                    }
+                                    let pin = match column_index < pinned {
+                                        true => "Unpin column",
+                                        false => "Pin column",
+                                    };
+                                    if ui.button(pin).clicked() {
+                                        action =
+                                            Some(TableAction::PinColumn(column_name.to_string()));
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Manage columns...").clicked() {
+                                        action = Some(TableAction::ManageColumns);
+                                        ui.close_menu();
+                                    }
+                                    ui.separator();
+                                    if ui.button("Conditional formatting...").clicked() {
+                                        action =
+                                            Some(TableAction::Conditional(column_name.to_string()));
+                                        ui.close_menu();
+                                    }
+                                }
                             });
-                        }
+                        });
                     });
-                });
-            }
-        };
+                }
+            };
+
+            // Defines a closure to render the table rows.
+            // This displays the data from each cell.
+            let analyze_rows = |mut table_row: TableRow<'_, '_>| {
+                let row_index = table_row.index(); // Gets the current row index.
+
+                let numbers = [
+                    Some(row_index + 1),
+                    file_rows.and_then(|rows| rows.get(row_index).map(|n| n as usize)),
+                ];
+                for number in &numbers[..number_columns] {
+                    table_row.col(|ui| {
+                        ui.with_layout(Layout::right_to_left(egui::Align::Center), |ui| {
+                            let text = number.map(|n| n.to_string()).unwrap_or_default();
+                            ui.add(Label::new(RichText::new(text).weak()).selectable(false));
+                        });
+                    });
+                }
+
+                // Iterate over the columns in the DataFrame.
+                for column_index in columns.clone() {
+                    let column = &self.df.get_columns()[column_index];
+                    // Format the value as the rules of its column say.
+                    let format = &formats[column_index];
+                    let value = column
+                        .get(row_index)
+                        .map(|any_value| format.format(any_value))
+                        .unwrap_or_else(|_| "Error: This is not a value!".to_string());
+                    let layout = format.align.layout();
+
+                    // Add the cell to the table row.
+                    let position = (row_index, column_index);
+                    let (_rect, response) = table_row.col(|ui| {
+                        if let Some(color) = colors.color(row_index, column.name()) {
+                            ui.painter().rect_filled(ui.max_rect(), 0.0, color);
+                        }
 
-        // Defines a closure to render the table rows.
-        // This displays the data from each cell.
-        let analyze_rows = |mut table_row: TableRow<'_, '_>| {
-            let row_index = table_row.index(); // Gets the current row index.
-
-            // Iterate over the columns in the DataFrame.
-            for column in self.df.get_columns() {
-                // Convert the AnyValue in the cell to a String for display.
-                let mut value: String = column
-                    .get(row_index)
-                    .map(|any_value| {
-                        match any_value {
-                            AnyValue::String(s) => s.to_string(),
-                            AnyValue::Null => "".to_string(), // Display "" for Null values.
-                            av => av.to_string(), // Fallback to Debug formatting for other types.
+                        if state
+                            .selection
+                            .is_some_and(|s| s.contains(row_index, column_index))
+                        {
+                            let color = ui.visuals().selection.bg_fill.gamma_multiply(0.5);
+                            ui.painter().rect_filled(ui.max_rect(), 0.0, color);
                         }
-                    })
-                    .unwrap_or_else(|_| "Error: This is not a value!".to_string());
-
-                // Determine the layout based on the data type for alignment.
-                let layout = if column.dtype().is_float() {
-                    // Check if the column name contains "Alíquota" (tax rate in Portuguese)
-                    let col_aliquota = column.name().contains("Alíquota");
-
-                    // Convert string to floating point number and format it
-                    value = match value.trim().parse::<f64>() {
-                        Ok(float) => {
-                            // If column is Alíquota format to 4 decimal places, else to 2.
-                            if col_aliquota {
-                                format!("{float:0.4}")
-                            } else {
-                                format!("{float:0.2}")
-                            }
+
+                        // Highlight the cells matching the search.
+                        if state.matches.contains(&position) {
+                            let color = match state.current_match == Some(position) {
+                                true => Color32::from_rgba_unmultiplied(255, 140, 0, 160),
+                                false => Color32::from_rgba_unmultiplied(255, 210, 0, 70),
+                            };
+                            ui.painter().rect_filled(ui.max_rect(), 0.0, color);
                         }
-                        Err(_) => value, // If parsing fails, keep the original string.
-                    };
-
-                    // Align center if it's an "Alíquota" column, otherwise align right.
-                    if col_aliquota {
-                        Layout::centered_and_justified(egui::Direction::LeftToRight)
-                    } else {
-                        Layout::right_to_left(egui::Align::Center)
+
+                        // Display the value within the determined layout.
+                        // Disable wrapping to prevent text overflow.
+                        // The label does not take the clicks, which select the cell.
+                        ui.with_layout(layout.with_main_wrap(false), |ui| {
+                            ui.add(Label::new(value).selectable(false)); // Display the value.
+                        });
+                    });
+
+                    // Click selects the cell, Shift extends the range and Ctrl selects the row.
+                    let outside = !state
+                        .selection
+                        .is_some_and(|s| s.contains(row_index, column_index));
+                    if response.clicked() || (response.secondary_clicked() && outside) {
+                        clicked = Some((position, response.ctx.input(|i| i.modifiers)));
                     }
-                } else if column.dtype().is_integer() || column.dtype().is_date() {
-                    // Center integer values.
-                    Layout::centered_and_justified(egui::Direction::LeftToRight)
-                } else {
-                    // Default to left alignment for other data types.
-                    Layout::left_to_right(egui::Align::Center)
-                };
 
-                // Add the cell to the table row.
-                table_row.col(|ui| {
-                    // Display the value within the determined layout.
-                    // Disable wrapping to prevent text overflow.
-                    ui.with_layout(layout.with_main_wrap(false), |ui| {
-                        ui.label(value); // Display the value.
+                    response.context_menu(|ui| {
+                        ui.checkbox(&mut inspector, "Cell inspector");
+                        ui.separator();
+                        if ui.button("Copy (Ctrl+C)").clicked() {
+                            copy = Some(CopyFormat::Tsv {
+                                headers: copy_headers,
+                            });
+                            ui.close_menu();
+                        }
+                        ui.checkbox(&mut copy_headers, "Copy with headers");
+                        ui.separator();
+                        if ui.button("Copy as Markdown table").clicked() {
+                            copy = Some(CopyFormat::Markdown);
+                            ui.close_menu();
+                        }
+                        if ui.button("Copy as SQL IN list").clicked() {
+                            copy = Some(CopyFormat::SqlInList);
+                            ui.close_menu();
+                        }
                     });
-                });
+                }
+            };
+
+            // Build the table using egui_extras::TableBuilder.
+            let mut table = TableBuilder::new(ui)
+                .striped(false) // Disable striped rows.
+                .sense(Sense::click()) // Cells can be clicked to select them.
+                .columns(Column::auto().resizable(false), number_columns) // Row numbers.
+                .columns(column, columns.len()); // Set up the columns.
+
+            table = match part {
+                TablePart::Whole => table.column(Column::remainder()),
+                TablePart::Pinned => table
+                    .id_salt("pinned_columns")
+                    .vertical_scroll_offset(scroll_offset)
+                    .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden),
+                TablePart::Scrolled => table
+                    .id_salt("scrolled_columns")
+                    .vertical_scroll_offset(scroll_offset)
+                    .column(Column::remainder()),
+            };
+
+            if let Some(row) = scroll_to_row {
+                table = table.scroll_to_row(row, Some(egui::Align::Center)); // Show the row once.
             }
+
+            table
+                .auto_shrink([part == TablePart::Pinned, false]) // Only the pinned columns shrink to fit.
+                .min_scrolled_height(1000.0) // Set a minimum height for the table.
+                .header(header_height, analyze_header) // Render the table header.
+                .body(|body| {
+                    let num_rows = self.df.height();
+                    body.rows(text_height, num_rows, analyze_rows); // Render the table rows.
+                })
+                .state
+                .offset
+                .y
         };
 
-        // Build the table using egui_extras::TableBuilder.
-        TableBuilder::new(ui)
-            .striped(false) // Disable striped rows.
-            .columns(column, self.df.width()) // Set up the columns.
-            .column(Column::remainder())
-            .auto_shrink([false, false]) // Disable auto-shrinking to fit content.
-            .min_scrolled_height(1000.0) // Set a minimum height for the table.
-            .header(header_height, analyze_header) // Render the table header.
-            .body(|body| {
-                let num_rows = self.df.height();
-                body.rows(text_height, num_rows, analyze_rows); // Render the table rows.
+        if pinned == 0 && number_columns == 0 {
+            ScrollArea::horizontal().show(ui, |ui| render(ui, 0..width, TablePart::Whole));
+        } else {
+            // Keep both tables at the same vertical position, following the one that scrolled.
+            ui.horizontal_top(|ui| {
+                let pinned_offset = render(ui, 0..pinned, TablePart::Pinned);
+                let scrolled_offset = ScrollArea::horizontal()
+                    .show(ui, |ui| render(ui, pinned..width, TablePart::Scrolled))
+                    .inner;
+                state.scroll_offset = match pinned_offset != scroll_offset {
+                    true => pinned_offset,
+                    false => scrolled_offset,
+                };
             });
+        }
+
+        state.copy_headers = copy_headers;
+        state.inspector = inspector;
+        state.update_selection(clicked, selected_column);
+
+        // Ctrl+C copies the selection, unless a text field has the focus.
+        let copy_key = ui.input(|i| i.events.iter().any(|event| matches!(event, Event::Copy)))
+            && ui.memory(|memory| memory.focused().is_none());
+        if copy_key {
+            copy = copy.or(Some(CopyFormat::Tsv {
+                headers: state.copy_headers,
+            }));
+        }
+
+        if let (Some(format), Some(selection)) = (copy, &state.selection) {
+            match copy_selection(&self.df, selection, format) {
+                Ok(text) => ui.ctx().copy_text(text),
+                Err(error) => {
+                    action = Some(TableAction::Error(format!(
+                        "Error copying the selection: {error}"
+                    )))
+                }
+            }
+        }
 
-        filters // Returns the DataFilters if sorting has been applied.
+        action // Returns the action requested by the user, if any.
     }
 }
 
@@ -267,7 +765,20 @@ pub async fn file_dialog() -> Result<String, String> {
     let opt_file_handle = AsyncFileDialog::new().pick_file().await; // Open the file dialog.
 
     match opt_file_handle {
-        Some(file_handle) => Ok(file_handle.file_name()), // Return the filename if a file is selected.
-        None => Err("No file loaded.".to_string()),       // Return an error if no file is selected.
+        Some(file_handle) => Ok(file_handle.path().to_string_lossy().to_string()), // Return the path if a file is selected.
+        None => Err("No file loaded.".to_string()), // Return an error if no file is selected.
+    }
+}
+
+/// Asynchronously opens a save file dialog, suggesting `file_name`.
+pub async fn save_file_dialog(file_name: &str) -> Result<PathBuf, String> {
+    let opt_file_handle = AsyncFileDialog::new()
+        .set_file_name(file_name)
+        .save_file()
+        .await; // Open the save dialog.
+
+    match opt_file_handle {
+        Some(file_handle) => Ok(file_handle.path().to_path_buf()), // Return the chosen path.
+        None => Err("No file selected.".to_string()), // Return an error if the dialog was cancelled.
     }
 }
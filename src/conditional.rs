@@ -0,0 +1,336 @@
+use crate::{CellFormat, FormatSettings, PendingTask};
+
+use egui::{Color32, ComboBox, Context, TextEdit, Ui};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Condition under which a rule colors a cell (or its row).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// The number is negative.
+    Negative,
+    /// The value is null.
+    Null,
+    /// The value, as text, equals the given one.
+    Equals(String),
+    /// Every value is colored, more intensely from the minimum to the maximum.
+    ColorScale,
+}
+
+impl Condition {
+    /// Name shown by the editor.
+    fn label(&self) -> &'static str {
+        match self {
+            Condition::Negative => "Negative",
+            Condition::Null => "Null",
+            Condition::Equals(_) => "Equals",
+            Condition::ColorScale => "Color scale",
+        }
+    }
+
+    /// Polars expression evaluating the condition on a column shown with `format`.
+    ///
+    /// Conditions give a boolean per row; the color scale gives the position of the
+    /// value between the minimum (0.0) and the maximum (1.0).
+    /// `Equals` compares the text of the cells, as the table shows it.
+    pub fn expr(&self, column: &str, format: &CellFormat) -> Expr {
+        let value = col(column);
+        match self {
+            Condition::Negative => value.lt(lit(0)),
+            Condition::Null => value.is_null(),
+            Condition::Equals(text) => format.expr(column).eq(lit(text.clone())),
+            Condition::ColorScale => {
+                let value = value.cast(DataType::Float64);
+                let (min, max) = (value.clone().min(), value.clone().max());
+                (value - min.clone()) / (max - min)
+            }
+        }
+    }
+}
+
+/// Conditional formatting rule of a column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConditionalRule {
+    /// Column whose values are tested.
+    pub column: String,
+    pub condition: Condition,
+    /// Background color, as sRGB.
+    pub color: [u8; 3],
+    /// Color the whole row instead of the cell.
+    pub whole_row: bool,
+}
+
+impl ConditionalRule {
+    /// Creates a rule coloring the negative numbers of a column in red.
+    pub fn new(column: &str) -> Self {
+        Self {
+            column: column.to_string(),
+            condition: Condition::Negative,
+            color: [200, 40, 40],
+            whole_row: false,
+        }
+    }
+}
+
+/// Result of a rule on every row of a DataFrame.
+#[derive(Debug, Clone)]
+enum RuleValues {
+    Matches(BooleanChunked),
+    Scale(Float64Chunked),
+}
+
+/// Colors given by the conditional formatting rules to the cells of a DataFrame.
+#[derive(Debug, Clone, Default)]
+pub struct CellColors {
+    /// Rules that could be evaluated, with their results.
+    rules: Vec<(ConditionalRule, RuleValues)>,
+    /// Why the other rules could not be evaluated.
+    pub errors: Vec<String>,
+}
+
+impl CellColors {
+    /// Evaluates the rules on `df`, whose cells are shown with `formats`.
+    pub fn evaluate(df: &DataFrame, rules: &[ConditionalRule], formats: &FormatSettings) -> Self {
+        let mut colors = CellColors::default();
+
+        for rule in rules {
+            let format = match df.column(&rule.column) {
+                Ok(column) => formats.resolve(column.name(), column.dtype()),
+                Err(_) => CellFormat::default(), // Reported by the evaluation.
+            };
+            let values = df
+                .clone()
+                .lazy()
+                .select([rule.condition.expr(&rule.column, &format).alias("values")])
+                .collect()
+                .and_then(|result| {
+                    let values = result.column("values")?;
+                    match rule.condition {
+                        Condition::ColorScale => Ok(RuleValues::Scale(values.f64()?.clone())),
+                        _ => Ok(RuleValues::Matches(values.bool()?.clone())),
+                    }
+                });
+
+            match values {
+                Ok(values) => colors.rules.push((rule.clone(), values)),
+                Err(error) => colors.errors.push(format!("{}: {}", rule.column, error)),
+            }
+        }
+
+        colors
+    }
+
+    /// Background color of a cell, if any rule applies to it.
+    ///
+    /// Cell rules of the column come before the row rules; the last matching rule wins.
+    pub fn color(&self, row: usize, column: &str) -> Option<Color32> {
+        let cell = self
+            .rules
+            .iter()
+            .filter(|(rule, _)| !rule.whole_row && rule.column == column);
+        let row_rules = self.rules.iter().filter(|(rule, _)| rule.whole_row);
+
+        cell.chain(row_rules)
+            .filter_map(|(rule, values)| {
+                let [r, g, b] = rule.color;
+                let alpha = match values {
+                    RuleValues::Matches(matches) => match matches.get(row) {
+                        Some(true) => 120.0,
+                        _ => return None,
+                    },
+                    RuleValues::Scale(scale) => 30.0 + 170.0 * scale.get(row)?.clamp(0.0, 1.0),
+                };
+                Some(Color32::from_rgba_unmultiplied(r, g, b, alpha as u8))
+            })
+            .next_back()
+    }
+}
+
+/// Rules of the table, with their colors on the last data they were evaluated on.
+#[derive(Default)]
+pub struct ConditionalFormatting {
+    pub rules: Vec<ConditionalRule>,
+    /// Data, rules and formats of the last evaluation.
+    evaluated: Option<(Arc<DataFrame>, Vec<ConditionalRule>, FormatSettings)>,
+    /// Evaluation still running.
+    evaluation: Option<PendingTask<CellColors>>,
+    /// Colors of the last finished evaluation, shown until the next one finishes.
+    colors: CellColors,
+}
+
+impl std::fmt::Debug for ConditionalFormatting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConditionalFormatting")
+            .field("rules", &self.rules)
+            .field("colors", &self.colors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConditionalFormatting {
+    /// Evaluates the rules in the background when the data, the rules or the formats change.
+    ///
+    /// One evaluation runs at a time: changes made meanwhile, such as typing the text of an
+    /// `Equals` rule, are evaluated together once it finishes.
+    pub fn update(
+        &mut self,
+        df: &Arc<DataFrame>,
+        formats: &FormatSettings,
+        runtime: &Handle,
+        ctx: &Context,
+    ) {
+        let finished = self
+            .evaluation
+            .as_mut()
+            .is_some_and(|task| task.poll().is_some());
+        if finished {
+            if let Some(PendingTask::Finished(Ok(colors))) = self.evaluation.take() {
+                self.colors = colors;
+            }
+        }
+
+        let up_to_date = matches!(&self.evaluated, Some((evaluated, rules, evaluated_formats))
+            if Arc::ptr_eq(evaluated, df) && *rules == self.rules && evaluated_formats == formats);
+
+        if !up_to_date && self.evaluation.is_none() {
+            let (data, rules, cell_formats) = (df.clone(), self.rules.clone(), formats.clone());
+            let future = async move { Ok(CellColors::evaluate(&data, &rules, &cell_formats)) };
+            self.evaluation = Some(PendingTask::spawn(runtime, ctx, future));
+            self.evaluated = Some((df.clone(), self.rules.clone(), formats.clone()));
+        }
+    }
+
+    /// Colors of the cells given by the last finished evaluation.
+    pub fn colors(&self) -> &CellColors {
+        &self.colors
+    }
+
+    /// Renders the editor of the rules of a column.
+    ///
+    /// Returns true when the rules changed.
+    pub fn render_editor(&mut self, ui: &mut Ui, column: &str) -> bool {
+        let before = self.rules.clone();
+        let mut removed: Option<usize> = None;
+
+        for (index, rule) in self.rules.iter_mut().enumerate() {
+            if rule.column != column {
+                continue;
+            }
+
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_salt("condition")
+                        .selected_text(rule.condition.label())
+                        .show_ui(ui, |ui| {
+                            for condition in [
+                                Condition::Negative,
+                                Condition::Null,
+                                Condition::Equals(String::new()),
+                                Condition::ColorScale,
+                            ] {
+                                let label = condition.label();
+                                ui.selectable_value(&mut rule.condition, condition, label);
+                            }
+                        });
+
+                    if let Condition::Equals(text) = &mut rule.condition {
+                        ui.add(TextEdit::singleline(text).desired_width(100.0));
+                    }
+
+                    ui.color_edit_button_srgb(&mut rule.color);
+                    ui.checkbox(&mut rule.whole_row, "Whole row");
+
+                    if ui.button("🗑").on_hover_text("Remove rule").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            });
+        }
+
+        if let Some(index) = removed {
+            self.rules.remove(index);
+        }
+
+        if ui.button("Add rule").clicked() {
+            self.rules.push(ConditionalRule::new(column));
+        }
+
+        let prefix = format!("{column}: ");
+        for error in self.colors.errors.iter().filter(|e| e.starts_with(&prefix)) {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        self.rules != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(column: &str, condition: Condition, whole_row: bool) -> ConditionalRule {
+        ConditionalRule {
+            condition,
+            whole_row,
+            ..ConditionalRule::new(column)
+        }
+    }
+
+    #[test]
+    fn test_cell_and_row_rules() -> PolarsResult<()> {
+        let df = df![
+            "Tipo" => [Some("Entrada"), Some("Saída"), None],
+            "Valor" => [10.0, -5.0, 3.0],
+        ]?;
+        let rules = [
+            rule("Valor", Condition::Negative, false),
+            rule("Tipo", Condition::Equals("Saída".to_string()), true),
+            rule("Tipo", Condition::Null, false),
+        ];
+        let colors = CellColors::evaluate(&df, &rules, &FormatSettings::default());
+        assert!(colors.errors.is_empty());
+
+        assert_eq!(colors.color(0, "Valor"), None);
+        assert_eq!(colors.color(2, "Valor"), None);
+        assert!(colors.color(2, "Tipo").is_some()); // Null.
+
+        // The row rule wins over the cell rule of "Valor".
+        let red = Color32::from_rgba_unmultiplied(200, 40, 40, 120);
+        assert_eq!(colors.color(1, "Valor"), Some(red));
+        assert_eq!(colors.color(1, "Tipo"), Some(red));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_color_scale_and_errors() -> PolarsResult<()> {
+        let df = df!["Valor" => [0.0, 5.0, 10.0], "Nome" => ["a", "b", "c"]]?;
+        let rules = [
+            rule("Valor", Condition::ColorScale, false),
+            rule("Ausente", Condition::Null, false),
+        ];
+        let colors = CellColors::evaluate(&df, &rules, &FormatSettings::default());
+
+        let alpha = |row| colors.color(row, "Valor").map(|color| color.a());
+        assert!(alpha(0) < alpha(1) && alpha(1) < alpha(2));
+        assert_eq!(colors.errors.len(), 1);
+        assert!(colors.errors[0].starts_with("Ausente: "));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_equals_compares_the_shown_text() -> PolarsResult<()> {
+        let df = df!["Valor" => [1.5, 2.0]]?;
+        let rules = [rule("Valor", Condition::Equals("1.50".to_string()), false)];
+
+        // Floats are shown with two decimals by default.
+        let colors = CellColors::evaluate(&df, &rules, &FormatSettings::default());
+        assert!(colors.color(0, "Valor").is_some());
+        assert_eq!(colors.color(1, "Valor"), None);
+
+        Ok(())
+    }
+}
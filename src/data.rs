@@ -1,15 +1,91 @@
-use crate::{Arguments, SQL_COMMANDS, get_extension};
-use egui::{
-    Align, CollapsingHeader, Color32, Frame, Grid, Hyperlink, Layout, Stroke, TextEdit, Ui, Vec2,
+use crate::{
+    Arguments, ColumnFilter, Completion, CsvOptions, ErrorLocation, FileMetadata, get_extension,
+    render_sql_editor, split_statements,
 };
+use egui::{Align, Context, Grid, Layout, TextEdit, Ui, Vec2};
 use polars::{prelude::*, sql::SQLContext};
-use std::{fs::File, future::Future, sync::Arc};
+use serde::{Deserialize, Serialize};
+use std::{fs::File, future::Future, path::Path, sync::Arc};
+use tokio::{
+    runtime::Handle,
+    sync::oneshot::{self, Receiver, error::TryRecvError},
+    task::JoinHandle,
+};
+
+/// Name of the column numbering the rows of a loaded file, kept in `DataFrameContainer::source`.
+const ROW_NUMBER: &str = "__polars_view_row_number__";
+
+/// Name of the column numbering the rows of a file sampled by a preview.
+const PREVIEW_INDEX: &str = "__polars_view_preview_index__";
 
 pub type DataResult = Result<DataFrameContainer, String>;
 pub type DataFuture = Box<dyn Future<Output = DataResult> + Unpin + Send + 'static>;
 
-// Set values that will be interpreted as missing/null.
-static NULL_VALUES: &[&str] = &["", " ", "<N/D>", "*DIVERSOS*"];
+/// Result of a background task, polled by the UI on every frame.
+pub enum PendingTask<T> {
+    /// The task is still running.
+    Running(Receiver<Result<T, String>>),
+    /// The task has finished, successfully or not.
+    Finished(Result<T, String>),
+}
+
+/// Spawns `future` on the Tokio runtime and returns the channel that will receive its result.
+///
+/// The UI is repainted as soon as the result has been sent.
+pub fn spawn_task<T: Send + 'static>(
+    runtime: &Handle,
+    ctx: &Context,
+    future: impl Future<Output = Result<T, String>> + Send + 'static,
+) -> (Receiver<Result<T, String>>, JoinHandle<()>) {
+    // Create a oneshot channel for sending the result from the async task to the UI thread.
+    let (tx, rx) = oneshot::channel::<Result<T, String>>();
+
+    // Clone the context for use within the asynchronous task (to request repaints).
+    let ctx_clone = ctx.clone();
+
+    // Spawn an async task to run the future.
+    let handle = runtime.spawn(async move {
+        let data = future.await;
+        if tx.send(data).is_err() {
+            eprintln!("Receiver dropped before data could be sent."); // Handle potential error if the receiver is dropped.
+        }
+        ctx_clone.request_repaint(); // Request a repaint of the UI to display the result.
+    });
+
+    (rx, handle)
+}
+
+impl<T: Send + 'static> PendingTask<T> {
+    /// Starts a background task whose result will be polled by the UI.
+    pub fn spawn(
+        runtime: &Handle,
+        ctx: &Context,
+        future: impl Future<Output = Result<T, String>> + Send + 'static,
+    ) -> Self {
+        PendingTask::Running(spawn_task(runtime, ctx, future).0)
+    }
+}
+
+impl<T> PendingTask<T> {
+    /// Checks the channel and returns the result once it has arrived.
+    pub fn poll(&mut self) -> Option<&Result<T, String>> {
+        if let PendingTask::Running(receiver) = self {
+            match receiver.try_recv() {
+                Ok(result) => *self = PendingTask::Finished(result),
+                Err(TryRecvError::Empty) => return None, // Still running.
+                Err(TryRecvError::Closed) => {
+                    let message = "Data operation terminated without response.".to_string();
+                    *self = PendingTask::Finished(Err(message));
+                }
+            }
+        }
+
+        match self {
+            PendingTask::Finished(result) => Some(result),
+            PendingTask::Running(_) => None,
+        }
+    }
+}
 
 /// Represents the sorting state for a column.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -22,6 +98,94 @@ pub enum SortState {
     Descending(String),
 }
 
+/// Action requested from the query pane.
+#[derive(Debug, Clone)]
+pub enum QueryAction {
+    /// Run the query.
+    Apply(DataFilters),
+    /// Show the plan of the query, without running it.
+    Explain(DataFilters),
+}
+
+/// Logical plan of a query, as Polars describes it.
+#[derive(Debug, Clone)]
+pub struct QueryPlan {
+    pub query: String,
+    /// Plan as written, before the optimizations.
+    pub unoptimized: String,
+    /// Plan that runs, with predicate and projection pushdown, etc.
+    pub optimized: String,
+}
+
+/// An open file registered as a table of the SQL queries, besides the queried one.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SqlTable {
+    /// Name of the table in the queries.
+    pub name: String,
+    pub filename: String,
+    /// CSV delimiter (ignored for Parquet files).
+    pub csv_delimiter: Option<String>,
+    pub csv_options: CsvOptions,
+}
+
+/// Rows of the queried file a preview runs the query on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Preview {
+    /// The first rows.
+    First(usize),
+    /// Rows drawn at random, in the order of the file.
+    Sample(usize),
+}
+
+impl Preview {
+    /// Number of rows previewed by default.
+    pub const DEFAULT_ROWS: usize = 1000;
+
+    /// Keeps only the previewed rows of the queried file.
+    ///
+    /// The rows are counted without reading their values, and only the previewed ones are
+    /// kept while the file is read.
+    fn apply(self, lazyframe: LazyFrame) -> Result<LazyFrame, String> {
+        match self {
+            Preview::First(rows) => Ok(lazyframe.limit(rows as IdxSize)),
+            Preview::Sample(rows) => {
+                let height = lazyframe
+                    .clone()
+                    .select([len()])
+                    .collect()
+                    .and_then(|df| df[0].get(0).map(|value| value.extract::<usize>()))
+                    .map_err(|e| format!("Preview error: {}", e))?
+                    .unwrap_or_default();
+
+                let indices = Series::new(PREVIEW_INDEX.into(), sample_indices(height, rows));
+                Ok(lazyframe
+                    .with_row_index(PREVIEW_INDEX, None)
+                    .filter(col(PREVIEW_INDEX).is_in(lit(indices)))
+                    .drop([PREVIEW_INDEX]))
+            }
+        }
+    }
+
+    /// Text shown with the result of a preview.
+    pub fn label(&self) -> String {
+        match self {
+            Preview::First(rows) => format!("Preview: query on the first {rows} rows of the file"),
+            Preview::Sample(rows) => format!("Preview: query on {rows} random rows of the file"),
+        }
+    }
+}
+
+/// Positions of `count` distinct rows out of `height`, drawn at random, in increasing order.
+pub fn sample_indices(height: usize, count: usize) -> Vec<IdxSize> {
+    let mut indices: Vec<IdxSize> =
+        rand::seq::index::sample(&mut rand::thread_rng(), height, count.min(height))
+            .into_iter()
+            .map(|index| index as IdxSize)
+            .collect();
+    indices.sort_unstable();
+    indices
+}
+
 /// Holds filters to be applied to the data.
 #[derive(Clone, Debug, Default)]
 pub struct DataFilters {
@@ -31,20 +195,29 @@ pub struct DataFilters {
     pub table_name: Option<String>,
     /// Optional CSV delimiter.
     pub csv_delimiter: Option<String>,
+    /// Options used to read CSV files.
+    pub csv_options: CsvOptions,
     /// Optional SQL query to apply to the data.
     pub query: Option<String>,
     /// Optional column sorting state.
     pub sort: Option<SortState>,
+    /// Filters built from the column headers, applied on top of the query result.
+    pub column_filters: Vec<ColumnFilter>,
+    /// Other open files, registered as tables of the query.
+    pub tables: Vec<SqlTable>,
+    /// Run the query on some rows of the file only.
+    pub preview: Option<Preview>,
 }
 
 impl DataFilters {
-    /// Creates a new `DataFilters` instance with a default configuration, including the filename.
-    pub fn new(filename: impl AsRef<str> + ToString) -> Self {
+    /// Creates a new `DataFilters` instance with a default configuration, including the filename
+    /// and the table name used by the queries.
+    pub fn new(filename: impl AsRef<str> + ToString, table_name: &str) -> Self {
         DataFilters {
             filename: Some(filename.to_string()),
-            table_name: Some("AllData".to_string()),
+            table_name: Some(table_name.to_string()),
             csv_delimiter: Some(";".to_string()),
-            query: Some(SQL_COMMANDS[0].to_string()),
+            query: Some(format!("SELECT * FROM {table_name};")),
             ..Default::default()
         }
     }
@@ -62,7 +235,15 @@ impl DataFilters {
     }
 
     /// Renders the query pane UI for configuring data filters.
-    pub fn render_filter(&mut self, ui: &mut Ui) -> Option<DataFilters> {
+    ///
+    /// `error` is the location of the last SQL error, underlined in the query;
+    /// `completions` are the names suggested while typing it.
+    pub fn render_filter(
+        &mut self,
+        ui: &mut Ui,
+        error: &mut Option<ErrorLocation>,
+        completions: &[Completion],
+    ) -> Option<QueryAction> {
         // Create mutable copies of the filter values to allow editing.
         let mut filename = self.filename.clone()?;
         let mut table_name = self.table_name.clone()?;
@@ -73,6 +254,14 @@ impl DataFilters {
 
         // Create a grid layout for the filter configuration.
         let mut result = None; // Mover a declaração para fora do Grid
+        let mut apply = false; // Apply button or Ctrl+Enter.
+
+        // Rows and kind of the previews, kept between frames.
+        let preview_id = ui.id().with("preview");
+        let (mut preview_rows, mut random) = ui
+            .data(|data| data.get_temp(preview_id))
+            .unwrap_or((Preview::DEFAULT_ROWS, false));
+        let mut preview = None;
 
         let grid = Grid::new("data_filters_grid")
             .num_columns(2)
@@ -105,27 +294,60 @@ impl DataFilters {
                 ui.end_row();
 
                 ui.label("SQL Query:");
-                let query_edit = TextEdit::multiline(&mut query).desired_width(width_max);
-                ui.add(query_edit)
-                    .on_hover_text("Enter SQL query to filter and transform the data...");
+                ui.vertical(|ui| apply = render_sql_editor(ui, &mut query, error, completions));
                 ui.end_row();
 
                 // Add the button to the grid.
                 ui.label(""); // Empty label to align with the label column.
                 ui.with_layout(Layout::top_down(Align::Center), |ui| {
-                    if ui.button("Apply SQL Commands").clicked() {
+                    let (button, explain) = ui
+                        .horizontal(|ui| {
+                            let apply = ui.button("Apply SQL Commands").on_hover_text("Ctrl+Enter");
+                            let explain = ui
+                                .button("Explain")
+                                .on_hover_text("Show the query plan, without running the query")
+                                .clicked();
+                            (apply, explain)
+                        })
+                        .inner;
+                    ui.horizontal(|ui| {
+                        let button = ui
+                            .button("Preview")
+                            .on_hover_text("Run the query on some rows of the file only");
+                        ui.add(
+                            egui::DragValue::new(&mut preview_rows)
+                                .range(1..=10_000_000)
+                                .suffix(" rows"),
+                        );
+                        ui.checkbox(&mut random, "Random sample");
+                        if button.clicked() {
+                            preview = Some(match random {
+                                true => Preview::Sample(preview_rows),
+                                false => Preview::First(preview_rows),
+                            });
+                        }
+                    });
+                    if button.clicked() || apply || explain || preview.is_some() {
                         // Only create and return DataFilters if the required fields are not empty.
                         if !filename.trim().is_empty()
                             && !table_name.trim().is_empty()
                             && !csv_delimiter.trim().is_empty()
                             && !query.trim().is_empty()
                         {
-                            result = Some(DataFilters {
+                            let filters = DataFilters {
                                 filename: Some(filename.clone()),
                                 table_name: Some(table_name.clone()),
                                 csv_delimiter: Some(csv_delimiter.clone()),
+                                csv_options: self.csv_options.clone(),
                                 query: Some(query.clone()),
                                 sort: self.sort.clone(), // Preserve existing sort state
+                                column_filters: Vec::new(), // Refer to the previous result
+                                tables: Vec::new(), // Filled in with the other open files.
+                                preview,
+                            };
+                            result = Some(match explain {
+                                true => QueryAction::Explain(filters),
+                                false => QueryAction::Apply(filters),
                             });
                         } else {
                             // Handle the case where required fields are empty.
@@ -140,36 +362,25 @@ impl DataFilters {
             });
         });
 
+        ui.data_mut(|data| data.insert_temp(preview_id, (preview_rows, random)));
+
         // Update the filter values with the edited values.
         self.filename = Some(filename);
         self.table_name = Some(table_name);
         self.csv_delimiter = Some(csv_delimiter);
         self.query = Some(query);
 
-        // Collapsing header for SQL command examples.
-        CollapsingHeader::new("SQL Command Examples:")
-            .default_open(false) // Initially collapsed.
-            .show(ui, |ui| {
-                // Highlighted frame for displaying SQL command examples.
-                Frame::default()
-                    .stroke(Stroke::new(1.0, Color32::GRAY))
-                    .outer_margin(2.0)
-                    .inner_margin(10.0)
-                    .show(ui, |ui| {
-                        ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
-                            let url =
-                                "https://docs.pola.rs/api/python/stable/reference/sql/index.html";
-                            let heading = Hyperlink::from_label_and_url("SQL Interface", url);
-                            ui.add(heading).on_hover_text(url);
-                        });
-                        ui.add(egui::Label::new(SQL_COMMANDS.join("\n\n")).selectable(true));
-                    });
-            });
-
         result // Retorne o resultado
     }
 }
 
+/// Table created by a statement of the query (`CREATE TABLE name AS SELECT ...`).
+#[derive(Debug, Clone)]
+pub struct CreatedTable {
+    pub name: String,
+    pub schema: SchemaRef,
+}
+
 /// Contains a DataFrame along with associated metadata and filters.
 #[derive(Debug, Clone)]
 pub struct DataFrameContainer {
@@ -177,34 +388,119 @@ pub struct DataFrameContainer {
     pub filename: String,
     /// The Polars DataFrame, wrapped in an Arc for shared ownership and thread-safe access.
     pub df: Arc<DataFrame>,
+    /// The DataFrame before the column filters are applied.
+    pub source: Arc<DataFrame>,
+    /// Row of the file (from 1) of each row of the DataFrame, when known.
+    pub row_numbers: Option<Column>,
     /// Filters applied to the DataFrame.
     pub filters: DataFilters,
+    /// Tables created by the statements of the query that produced the DataFrame.
+    pub created_tables: Vec<CreatedTable>,
+    /// Metadata of the file, when read by the task that produced the DataFrame.
+    pub metadata: Option<Arc<FileMetadata>>,
 }
 
 impl DataFrameContainer {
+    /// Creates a container whose data has no column filter applied yet.
+    ///
+    /// When `df` numbers the rows of its file (see `number_rows`), the numbers are kept
+    /// apart from the data.
+    pub fn new(filename: String, df: DataFrame, filters: DataFilters) -> Self {
+        let source = Arc::new(df);
+        let (df, row_numbers) = split_row_numbers(source.as_ref().clone());
+
+        Self {
+            filename,
+            df: Arc::new(df),
+            source,
+            row_numbers,
+            filters,
+            created_tables: Vec::new(),
+            metadata: None,
+        }
+    }
+
+    /// Reads the metadata of the file of the data, counting its rows unless `num_rows`
+    /// is known.  Files without metadata are left without it.
+    pub fn with_metadata(mut self, num_rows: Option<usize>) -> Self {
+        let csv_delimiter = self.filters.csv_delimiter.as_deref();
+        let csv_options = &self.filters.csv_options;
+        let metadata =
+            FileMetadata::from_filename(&self.filename, csv_delimiter, csv_options, num_rows);
+        self.metadata = metadata.ok().map(Arc::new);
+        self
+    }
+
+    /// Whether the data comes from the same file, read the same way, as the one the filters query.
+    pub fn same_source(&self, filters: &DataFilters) -> bool {
+        filters.filename.as_ref() == Some(&self.filename)
+            && filters.csv_delimiter == self.filters.csv_delimiter
+            && filters.csv_options == self.filters.csv_options
+    }
+
+    /// Numbers the rows of a file just read, from 1.
+    fn number_rows(df: DataFrame) -> Result<DataFrame, String> {
+        df.with_row_index(ROW_NUMBER.into(), Some(1))
+            .map_err(|e| format!("Error numbering the rows: {}", e))
+    }
+
+    /// The DataFrame with the row numbers as an extra column, to keep them through a sort.
+    fn with_row_numbers(&self) -> PolarsResult<DataFrame> {
+        let mut df = self.df.as_ref().clone();
+        if let Some(row_numbers) = &self.row_numbers {
+            df.with_column(row_numbers.clone())?;
+        }
+        Ok(df)
+    }
+
+    /// Position in the DataFrame of the given row of the file, or of the DataFrame itself.
+    ///
+    /// Rows are numbered from 1. Returns None when the row is not in the current data.
+    pub fn row_position(&self, row: usize, file_row: bool) -> Option<usize> {
+        match (file_row, &self.row_numbers) {
+            (true, Some(row_numbers)) => {
+                let row_numbers = row_numbers.idx().ok()?;
+                row_numbers
+                    .iter()
+                    .position(|number| number.map(|n| n as usize) == Some(row))
+            }
+            _ => (1..=self.df.height()).contains(&row).then(|| row - 1),
+        }
+    }
+
     /// Loads data from a file (Parquet or CSV) using Polars.
-    pub async fn load_data(filename: impl AsRef<str>) -> Result<Self, String> {
+    pub async fn load_data(
+        filename: impl AsRef<str>,
+        csv_options: CsvOptions,
+    ) -> Result<Self, String> {
         let filename = shellexpand::full(&filename)
             .map_err(|err| err.to_string())?
             .to_string();
 
         dbg!(&filename);
 
+        let mut filters = DataFilters {
+            csv_options,
+            ..Default::default()
+        };
+
         // Determine file type based on extension and load accordingly.
         let df = match get_extension(&filename).as_deref() {
-            Some("parquet") => Self::read_parquet(&filename).await,
-            Some("csv") => Self::read_csv(&filename).await,
+            Some("parquet") => Self::read_parquet(&filename).await?,
+            Some("csv") => {
+                let (df, delimiter) = Self::read_csv(&filename, &filters.csv_options).await?;
+                // Keep the detected delimiter, so that queries and metadata reuse it.
+                filters.csv_delimiter = Some((delimiter as char).to_string());
+                df
+            }
             _ => {
                 let msg = format!("Unknown file type: {:#?}", filename);
                 return Err(msg);
             }
-        }?;
+        };
 
-        Ok(Self {
-            filename,
-            df: Arc::new(df),
-            filters: DataFilters::default(),
-        })
+        let num_rows = df.height();
+        Ok(Self::new(filename, Self::number_rows(df)?, filters).with_metadata(Some(num_rows)))
     }
 
     /// Reads a Parquet file into a Polars DataFrame.
@@ -218,37 +514,44 @@ impl DataFrameContainer {
     }
 
     /// Attempts to read a CSV file with different delimiters until successful.
-    async fn read_csv(filename: &str) -> Result<DataFrame, String> {
-        // Delimiters to attempt when reading CSV files.
-        let delimiters = [b',', b';', b'|', b'\t'];
+    ///
+    /// Returns the DataFrame together with the delimiter that produced it.
+    async fn read_csv(filename: &str, options: &CsvOptions) -> Result<(DataFrame, u8), String> {
+        // Delimiters to attempt when reading CSV files; only single bytes can be delimiters.
+        let delimiters = options.delimiters.chars().filter(char::is_ascii);
 
-        for delimiter in delimiters {
-            let result_df = Self::attempt_read_csv(filename, delimiter).await;
+        for delimiter in delimiters.map(|c| c as u8) {
+            let result_df = Self::attempt_read_csv(filename, delimiter, options).await;
 
             if let Ok(df) = result_df {
-                return Ok(df); // Return the DataFrame on success
+                return Ok((df, delimiter)); // Return the DataFrame on success
             }
         }
 
-        let msg = "Failed to read CSV with common delimiters or inconsistent data.";
+        let msg = "Failed to read CSV with the configured delimiters or inconsistent data.";
         eprintln!("{msg}");
         Err(msg.to_string())
     }
 
-    /// Attempts to read a CSV file using a specific delimiter.
-    async fn attempt_read_csv(filename: &str, delimiter: u8) -> Result<DataFrame, String> {
-        dbg!(&filename, delimiter as char);
-
+    /// Lazily scans a CSV file with the options shared by every CSV code path.
+    pub fn scan_csv(
+        filename: &str,
+        delimiter: u8,
+        options: &CsvOptions,
+    ) -> Result<LazyFrame, String> {
         // Set values that will be interpreted as missing/null.
-        let null_values: Vec<PlSmallStr> = NULL_VALUES.iter().map(|&s| s.into()).collect();
+        let null_values: Vec<PlSmallStr> = options
+            .null_values
+            .iter()
+            .map(|s| s.as_str().into())
+            .collect();
 
-        // Configure the CSV reader with flexible options.
-        let lazyframe = LazyCsvReader::new(filename)
+        LazyCsvReader::new(filename)
             .with_encoding(CsvEncoding::LossyUtf8) // Handle various encodings
             .with_has_header(true) // Assume the first row is a header
-            .with_try_parse_dates(true) // use regex
+            .with_try_parse_dates(options.try_parse_dates) // use regex
             .with_separator(delimiter) // Set the delimiter
-            .with_infer_schema_length(Some(200)) // Limit schema inference to the first 200 rows.
+            .with_infer_schema_length(Some(options.infer_schema_length)) // Limit schema inference to the first rows.
             .with_ignore_errors(true) // Ignore parsing errors
             .with_missing_is_null(true) // Treat missing values as null
             .with_null_values(Some(NullValues::AllColumns(null_values)))
@@ -258,7 +561,19 @@ impl DataFrameContainer {
                     "Error reading CSV with delimiter '{}': {}",
                     delimiter as char, e
                 )
-            })?;
+            })
+    }
+
+    /// Attempts to read a CSV file using a specific delimiter.
+    async fn attempt_read_csv(
+        filename: &str,
+        delimiter: u8,
+        options: &CsvOptions,
+    ) -> Result<DataFrame, String> {
+        dbg!(&filename, delimiter as char);
+
+        // Configure the CSV reader with flexible options.
+        let lazyframe = Self::scan_csv(filename, delimiter, options)?;
 
         // Collect the lazy DataFrame into a DataFrame
         let df = lazyframe
@@ -294,6 +609,37 @@ impl DataFrameContainer {
     pub async fn load_data_with_sql(filters: DataFilters) -> Result<Self, String> {
         dbg!(&filters);
 
+        // Execute the query and collect the results
+        let (filename, lazyframe, created_tables) = Self::sql_lazyframe(&filters)?;
+        let sql_df: DataFrame = lazyframe
+            .collect()
+            .map_err(|e| format!("DataFrame error: {}", e))?;
+
+        Ok(Self {
+            created_tables,
+            ..Self::new(filename, sql_df, filters)
+        })
+    }
+
+    /// Describes the logical plan of a SQL query, before and after the optimizations,
+    /// without running it.
+    pub async fn explain_sql(filters: DataFilters) -> Result<QueryPlan, String> {
+        let (_, lazyframe, _) = Self::sql_lazyframe(&filters)?;
+        let describe = |plan: PolarsResult<String>| plan.map_err(|e| format!("Plan error: {}", e));
+
+        Ok(QueryPlan {
+            query: filters.query.clone().unwrap_or_default(),
+            unoptimized: describe(lazyframe.describe_plan())?,
+            optimized: describe(lazyframe.describe_optimized_plan())?,
+        })
+    }
+
+    /// Registers the files of the query in a SQL context and runs its statements, returning
+    /// the expanded filename of the queried file, the lazy result of the last statement and
+    /// the tables created by the others.
+    fn sql_lazyframe(
+        filters: &DataFilters,
+    ) -> Result<(String, LazyFrame, Vec<CreatedTable>), String> {
         // Extract required parameters from filters
         let Some(filename) = filters.filename.clone() else {
             return Err("No filename".to_string());
@@ -315,64 +661,93 @@ impl DataFrameContainer {
             .map_err(|err| err.to_string())?
             .to_string();
 
-        // Load the DataFrame from the file
-        let df: DataFrame = match get_extension(&filename).as_deref() {
-            Some("parquet") => Self::read_parquet(&filename).await?,
+        // Create a SQL context and register the queried file
+        let mut ctx = SQLContext::new();
+        let mut lazyframe = Self::scan_file(&filename, Some(&csv_delimiter), &filters.csv_options)?;
+        if let Some(preview) = filters.preview {
+            lazyframe = preview.apply(lazyframe)?; // Only some rows of the queried file.
+        }
+        ctx.register(&table_name, lazyframe);
+
+        // Register the other open files, so that the query can join them.
+        for table in &filters.tables {
+            if table.name == table_name || table.name.trim().is_empty() {
+                continue; // The queried file keeps its name.
+            }
+            let delimiter = table.csv_delimiter.as_deref();
+            let lazyframe = Self::scan_file(&table.filename, delimiter, &table.csv_options)
+                .map_err(|e| format!("Table {}: {}", table.name, e))?;
+            ctx.register(&table.name, lazyframe);
+        }
+
+        // Run the statements in order: `CREATE TABLE x AS SELECT ...` registers x for the
+        // next ones, and the last one gives the result.
+        let registered = ctx.get_tables();
+        let mut lazyframe = None;
+        for range in split_statements(query) {
+            // Blank the statements before, so that errors give the lines and columns of the script.
+            let blank: String = query[..range.start]
+                .chars()
+                .map(|c| if c == '\n' { c } else { ' ' })
+                .collect();
+            let statement = blank + &query[range];
+            let result = ctx
+                .execute(&statement)
+                .map_err(|e| format!("Polars SQL error: {}", e))?;
+            lazyframe = Some(result);
+        }
+        let lazyframe = lazyframe.ok_or("No query provided")?;
+
+        let mut created_tables = Vec::new();
+        for name in ctx.get_tables() {
+            if registered.contains(&name) {
+                continue;
+            }
+            let schema = ctx
+                .execute(&format!("SELECT * FROM \"{}\"", name.replace('"', "\"\"")))
+                .and_then(|mut table| table.collect_schema())
+                .map_err(|e| format!("Table {}: {}", name, e))?;
+            created_tables.push(CreatedTable { name, schema });
+        }
+
+        Ok((filename, lazyframe, created_tables))
+    }
+
+    /// Scans a Parquet or CSV file lazily, reading CSV files with the given delimiter.
+    fn scan_file(
+        filename: &str,
+        csv_delimiter: Option<&str>,
+        options: &CsvOptions,
+    ) -> Result<LazyFrame, String> {
+        match get_extension(filename).as_deref() {
+            Some("parquet") => LazyFrame::scan_parquet(filename, ScanArgsParquet::default())
+                .map_err(|e| format!("Error reading parquet: {}", e)),
             Some("csv") => {
                 // Convert csv_delimiter string to u8 delimiter
-                let delimiter: u8 = match csv_delimiter.len() {
-                    1 => csv_delimiter.as_bytes()[0],
+                let delimiter: u8 = match csv_delimiter.map(str::as_bytes) {
+                    Some(&[delimiter]) => delimiter,
                     _ => {
                         let msg = "Error: The CSV delimiter must be a single character.";
                         return Err(msg.to_string());
                     }
                 };
 
-                // Set values that will be interpreted as missing/null.
-                let null_values: Vec<PlSmallStr> = NULL_VALUES.iter().map(|&s| s.into()).collect();
-
                 // Read CSV using the specified delimiter
-                let lazyframe = LazyCsvReader::new(&filename)
-                    .with_encoding(CsvEncoding::LossyUtf8) // Handle various encodings
-                    .with_try_parse_dates(true) // use regex
-                    .with_has_header(true) // Assume the first row is a header
-                    .with_separator(delimiter) // Set the delimiter
-                    .with_infer_schema_length(Some(200)) // Limit schema inference to the first 200 rows.
-                    .with_ignore_errors(true) // Ignore parsing errors
-                    .with_missing_is_null(true) // Treat missing values as null
-                    .with_null_values(Some(NullValues::AllColumns(null_values)))
-                    .finish()
-                    .map_err(|e| {
-                        format!(
-                            "Error reading CSV with delimiter '{}': {}",
-                            delimiter as char, e
-                        )
-                    })?;
-
-                lazyframe.collect().map_err(|e| format!("Error: {}", e))?
-            }
-            _ => {
-                let msg = format!("Unknown file type: {}", filename);
-                return Err(msg);
+                Self::scan_csv(filename, delimiter, options)
             }
-        };
-
-        // Create a SQL context and register the DataFrame
-        let mut ctx = SQLContext::new();
-        ctx.register(&table_name, df.lazy());
+            _ => Err(format!("Unknown file type: {}", filename)),
+        }
+    }
 
-        // Execute the query and collect the results
-        let sql_df: DataFrame = ctx
-            .execute(query)
-            .map_err(|e| format!("Polars SQL error: {}", e))?
-            .collect()
-            .map_err(|e| format!("DataFrame error: {}", e))?;
+    /// Writes the DataFrame to a CSV file, using `;` as the delimiter.
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let mut file = File::create(path).map_err(|e| format!("Error creating file: {}", e))?;
 
-        Ok(Self {
-            filename,
-            df: Arc::new(sql_df),
-            filters,
-        })
+        CsvWriter::new(&mut file)
+            .include_header(true)
+            .with_separator(b';')
+            .finish(&mut self.df.as_ref().clone())
+            .map_err(|e| format!("Error writing CSV: {}", e))
     }
 
     /// Sorts the data based on the provided filters.
@@ -405,18 +780,27 @@ impl DataFrameContainer {
             .with_order_descending(!ascending) // Sort order: ascending or descending
             .with_nulls_last(false);
 
-        // Sort the DataFrame using Polars
-        self.df = Arc::new(
-            self.df
-                .sort([col_name], sort_options)
-                .map_err(|e| format!("Polars sort error: {}", e))?,
-        );
+        // Sort the DataFrame using Polars, together with the row numbers.
+        let sorted = self
+            .with_row_numbers()
+            .and_then(|df| df.sort([col_name], sort_options))
+            .map_err(|e| format!("Polars sort error: {}", e))?;
+        let (df, row_numbers) = split_row_numbers(sorted);
+
+        self.df = Arc::new(df);
+        self.row_numbers = row_numbers;
         self.filters = filters; //Update filters
 
         Ok(self)
     }
 }
 
+/// Separates the row numbers added by `DataFrameContainer::number_rows` from the data.
+pub fn split_row_numbers(mut df: DataFrame) -> (DataFrame, Option<Column>) {
+    let row_numbers = df.drop_in_place(ROW_NUMBER).ok();
+    (df, row_numbers)
+}
+
 // font: polars-0.46.0/tests/it/io/csv.rs
 #[test]
 fn test_quoted_bool_ints() -> PolarsResult<()> {
@@ -436,3 +820,195 @@ fn test_quoted_bool_ints() -> PolarsResult<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_row_numbers_follow_sort_and_filter() -> Result<(), String> {
+    let df = df!["name" => ["c", "a", "b", "a"]].map_err(|e| e.to_string())?;
+    let data = DataFrameContainer::new(
+        "data.csv".to_string(),
+        DataFrameContainer::number_rows(df)?,
+        DataFilters::default(),
+    );
+    assert_eq!(data.df.get_column_names(), ["name"]);
+
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let filters = DataFilters {
+        sort: Some(SortState::Ascending("name".to_string())),
+        column_filters: vec![ColumnFilter {
+            column: "name".to_string(),
+            condition: crate::FilterCondition::OneOf(vec!["a".to_string(), "c".to_string()]),
+        }],
+        ..Default::default()
+    };
+    let data = runtime.block_on(data.filter(filters))?;
+
+    let row_numbers = data.row_numbers.clone().ok_or("no row numbers")?;
+    let row_numbers: Vec<Option<IdxSize>> = row_numbers
+        .idx()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .collect();
+    assert_eq!(row_numbers, [Some(2), Some(4), Some(1)]);
+
+    assert_eq!(data.row_position(1, true), Some(2)); // "c", first in the file.
+    assert_eq!(data.row_position(3, true), None); // "b" was filtered out.
+    assert_eq!(data.row_position(3, false), Some(2));
+    assert_eq!(data.row_position(4, false), None);
+
+    Ok(())
+}
+
+/// Runs `test` with the path of an `invoices.csv` file holding `content`, in a temporary
+/// directory removed afterwards, and a runtime to load it.
+#[cfg(test)]
+fn with_invoices<T>(
+    content: &str,
+    test: impl FnOnce(&Path, &tokio::runtime::Runtime) -> Result<T, String>,
+) -> Result<T, String> {
+    static DIRS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let number = DIRS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("polars_view_{}_{number}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let invoices = dir.join("invoices.csv");
+    let result = std::fs::write(&invoices, content)
+        .map_err(|e| e.to_string())
+        .and_then(|_| tokio::runtime::Runtime::new().map_err(|e| e.to_string()))
+        .and_then(|runtime| test(&invoices, &runtime));
+    std::fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+    result
+}
+
+#[test]
+fn test_query_joins_other_tables() -> Result<(), String> {
+    let data = with_invoices(
+        "supplier;value\n1;10.5\n2;3.0\n1;4.5\n",
+        |invoices, runtime| {
+            let suppliers = invoices.with_file_name("suppliers.csv");
+            std::fs::write(&suppliers, "id,name\n1,Acme\n2,Globex\n").map_err(|e| e.to_string())?;
+
+            let filters = DataFilters {
+                filename: Some(invoices.to_string_lossy().to_string()),
+                table_name: Some("invoices".to_string()),
+                csv_delimiter: Some(";".to_string()),
+                query: Some(
+                    "SELECT name, SUM(value) AS total FROM invoices \
+                 JOIN suppliers ON invoices.supplier = suppliers.id \
+                 GROUP BY name ORDER BY name"
+                        .to_string(),
+                ),
+                tables: vec![SqlTable {
+                    name: "suppliers".to_string(),
+                    filename: suppliers.to_string_lossy().to_string(),
+                    csv_delimiter: Some(",".to_string()),
+                    csv_options: CsvOptions::default(),
+                }],
+                ..Default::default()
+            };
+            runtime.block_on(DataFrameContainer::load_data_with_sql(filters))
+        },
+    )?;
+
+    let expected =
+        df!["name" => ["Acme", "Globex"], "total" => [15.0, 3.0]].map_err(|e| e.to_string())?;
+    assert!(data.df.equals_missing(&expected));
+
+    Ok(())
+}
+
+#[test]
+fn test_explain_shows_pushdown() -> Result<(), String> {
+    let plan = with_invoices(
+        "supplier;value;note\n1;10.5;a\n2;3.0;b\n",
+        |invoices, runtime| {
+            let filters = DataFilters {
+                query: Some("SELECT value FROM invoices WHERE supplier = 1".to_string()),
+                ..DataFilters::new(invoices.to_string_lossy(), "invoices")
+            };
+            runtime.block_on(DataFrameContainer::explain_sql(filters))
+        },
+    )?;
+
+    // Plans are printed from the root down to the scan of the file: the predicate on
+    // `supplier` comes before the scan until it is pushed down into it.
+    let predicate_after_scan = |plan: &str| -> Option<bool> {
+        let scan = plan.find("SCAN")?;
+        Some(plan.find("col(\"supplier\")")? > scan)
+    };
+    assert_eq!(
+        predicate_after_scan(&plan.unoptimized),
+        Some(false),
+        "{}",
+        plan.unoptimized
+    );
+    assert_eq!(
+        predicate_after_scan(&plan.optimized),
+        Some(true),
+        "{}",
+        plan.optimized
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_script_creates_tables() -> Result<(), String> {
+    let content = "supplier;value\n1;10.5\n2;3.0\n1;4.5\n";
+    let (result, error) = with_invoices(content, |invoices, runtime| {
+        let script = "CREATE TABLE totals AS SELECT supplier, SUM(value) AS total \
+                      FROM invoices GROUP BY supplier; -- Intermediate result;\n\
+                      SELECT total FROM totals WHERE supplier = 1;";
+        let filters = DataFilters {
+            query: Some(script.to_string()),
+            ..DataFilters::new(invoices.to_string_lossy(), "invoices")
+        };
+        let result = runtime.block_on(DataFrameContainer::load_data_with_sql(filters.clone()));
+
+        // Errors give the line of the script, not of the statement.
+        let error = DataFilters {
+            query: Some("SELECT 1;\nSELECT * FORM invoices".to_string()),
+            ..filters
+        };
+        let error = runtime.block_on(DataFrameContainer::load_data_with_sql(error));
+        Ok((result, error))
+    })?;
+
+    let data = result?;
+    let expected = df!["total" => [15.0]].map_err(|e| e.to_string())?;
+    assert!(data.df.equals_missing(&expected));
+    let created: Vec<&str> = data
+        .created_tables
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+    assert_eq!(created, ["totals"]);
+
+    let location = error
+        .err()
+        .and_then(|message| ErrorLocation::parse(&message));
+    assert_eq!(location.map(|location| location.line), Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn test_preview_sample() -> Result<(), String> {
+    let df = df!["value" => [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]].map_err(|e| e.to_string())?;
+
+    let sample = Preview::Sample(4)
+        .apply(df.clone().lazy())
+        .and_then(|sample| sample.collect().map_err(|e| e.to_string()))?;
+    let values: Vec<i32> = sample["value"]
+        .i32()
+        .map_err(|e| e.to_string())?
+        .into_no_null_iter()
+        .collect();
+    assert_eq!(sample.get_column_names(), ["value"]);
+    assert_eq!(values.len(), 4);
+    assert!(values.windows(2).all(|pair| pair[0] < pair[1])); // Distinct, in file order.
+
+    let all = Preview::Sample(20).apply(df.clone().lazy())?.collect();
+    assert!(all.map_err(|e| e.to_string())?.equals(&df)); // Fewer rows than asked.
+
+    Ok(())
+}
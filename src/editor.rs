@@ -0,0 +1,610 @@
+use crate::{Completion, CompletionPopup, suggestions, word_before};
+
+use egui::{
+    Align2, Area, Color32, Frame, Id, Key, Modifiers, Order, Stroke, TextEdit, TextFormat,
+    TextStyle, Ui, pos2,
+    text::{CCursor, CCursorRange, LayoutJob},
+};
+use std::ops::Range;
+
+/// Keywords highlighted by the SQL editor.
+pub const SQL_KEYWORDS: &[&str] = &[
+    "ALL",
+    "AND",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CAST",
+    "CREATE",
+    "CROSS",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXCEPT",
+    "EXPLAIN",
+    "FALSE",
+    "FIRST",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "ILIKE",
+    "IN",
+    "INNER",
+    "INSERT",
+    "INTERSECT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "LAST",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NOT",
+    "NULL",
+    "NULLS",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "OVER",
+    "PARTITION",
+    "RIGHT",
+    "SELECT",
+    "SEMI",
+    "SHOW",
+    "TABLE",
+    "TABLES",
+    "THEN",
+    "TRUE",
+    "UNION",
+    "USING",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WITH",
+    "ANTI",
+    "EXCLUDE",
+];
+
+/// Kind of a piece of SQL text, which gives its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    /// A name followed by an opening parenthesis.
+    Function,
+    Identifier,
+    /// A name between backticks or double quotes.
+    QuotedIdentifier,
+    String,
+    Number,
+    Comment,
+    Bracket,
+    Operator,
+    Whitespace,
+}
+
+/// A piece of SQL text, with its byte range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub range: Range<usize>,
+}
+
+/// Splits SQL text into tokens, covering the whole text.
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_at = |index: usize| chars.get(index).map_or(text.len(), |(byte, _)| *byte);
+
+    // Number of chars, from the start of `rest`, satisfying `predicate` (at least one).
+    let run = |rest: &[(usize, char)], predicate: &dyn Fn(char) -> bool| {
+        1 + rest[1..].iter().take_while(|(_, c)| predicate(*c)).count()
+    };
+
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let rest = &chars[index..];
+        let next = rest.get(1).map(|(_, c)| *c);
+
+        let (kind, length) = match rest[0].1 {
+            c if c.is_whitespace() => (TokenKind::Whitespace, run(rest, &|c| c.is_whitespace())),
+            '-' if next == Some('-') => (TokenKind::Comment, run(rest, &|c| c != '\n')),
+            '/' if next == Some('*') => {
+                let end = rest
+                    .windows(2)
+                    .skip(2)
+                    .position(|w| w[0].1 == '*' && w[1].1 == '/');
+                (TokenKind::Comment, end.map_or(rest.len(), |end| end + 4))
+            }
+            '\'' => (TokenKind::String, quoted_length(rest)),
+            '`' | '"' => (TokenKind::QuotedIdentifier, quoted_length(rest)),
+            c if c.is_ascii_digit() => (
+                TokenKind::Number,
+                run(rest, &|c| c.is_ascii_alphanumeric() || c == '.'),
+            ),
+            c if c.is_alphabetic() || c == '_' => {
+                let length = run(rest, &|c| c.is_alphanumeric() || c == '_');
+                let word: String = rest[..length].iter().map(|(_, c)| *c).collect();
+                let called = rest[length..]
+                    .iter()
+                    .find(|(_, c)| !c.is_whitespace())
+                    .is_some_and(|(_, c)| *c == '(');
+
+                let kind = if SQL_KEYWORDS.contains(&word.to_uppercase().as_str()) {
+                    TokenKind::Keyword
+                } else if called {
+                    TokenKind::Function
+                } else {
+                    TokenKind::Identifier
+                };
+                (kind, length)
+            }
+            '(' | ')' | '[' | ']' | '{' | '}' => (TokenKind::Bracket, 1),
+            _ => (TokenKind::Operator, 1),
+        };
+
+        tokens.push(Token {
+            kind,
+            range: byte_at(index)..byte_at(index + length),
+        });
+        index += length;
+    }
+
+    tokens
+}
+
+/// Number of chars of the quoted text at the start of `rest`, quotes included.
+///
+/// A doubled quote stands for the quote itself; unclosed text runs to the end.
+fn quoted_length(rest: &[(usize, char)]) -> usize {
+    let quote = rest[0].1;
+    let mut index = 1;
+
+    while index < rest.len() {
+        if rest[index].1 == quote {
+            match rest.get(index + 1) {
+                Some((_, c)) if *c == quote => index += 2,
+                _ => return index + 1,
+            }
+        } else {
+            index += 1;
+        }
+    }
+
+    rest.len()
+}
+
+/// Byte offset of the char at `index` (the end of `text` past the last char).
+pub fn byte_offset(text: &str, index: usize) -> usize {
+    text.char_indices()
+        .nth(index)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// Byte ranges of the statements of a script, separated by `;`.
+///
+/// Semicolons in strings, names and comments do not separate statements; statements with
+/// only spaces and comments are left out.
+pub fn split_statements(text: &str) -> Vec<Range<usize>> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut empty = true;
+
+    for token in tokenize(text) {
+        match token.kind {
+            TokenKind::Operator if &text[token.range.clone()] == ";" => {
+                if !empty {
+                    statements.push(start..token.range.start);
+                }
+                (start, empty) = (token.range.end, true);
+            }
+            TokenKind::Whitespace | TokenKind::Comment => {}
+            _ => empty = false,
+        }
+    }
+
+    if !empty {
+        statements.push(start..text.len());
+    }
+    statements
+}
+
+/// Byte ranges of the bracket next to the cursor (a byte offset) and of its match.
+///
+/// The bracket just before the cursor comes first; brackets in strings and comments are ignored.
+pub fn matching_brackets(text: &str, cursor: usize) -> Option<(Range<usize>, Range<usize>)> {
+    let brackets: Vec<Token> = tokenize(text)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Bracket)
+        .collect();
+
+    let index = brackets
+        .iter()
+        .position(|token| token.range.end == cursor)
+        .or_else(|| {
+            brackets
+                .iter()
+                .position(|token| token.range.start == cursor)
+        })?;
+
+    let bracket = text[brackets[index].range.clone()].chars().next()?;
+    let (pair, candidates): (char, Box<dyn Iterator<Item = &Token>>) = match bracket {
+        '(' => (')', Box::new(brackets[index + 1..].iter())),
+        '[' => (']', Box::new(brackets[index + 1..].iter())),
+        '{' => ('}', Box::new(brackets[index + 1..].iter())),
+        ')' => ('(', Box::new(brackets[..index].iter().rev())),
+        ']' => ('[', Box::new(brackets[..index].iter().rev())),
+        _ => ('{', Box::new(brackets[..index].iter().rev())),
+    };
+
+    let mut depth = 0;
+    for token in candidates {
+        match text[token.range.clone()].chars().next() {
+            Some(c) if c == bracket => depth += 1,
+            Some(c) if c == pair && depth == 0 => {
+                return Some((brackets[index].range.clone(), token.range.clone()));
+            }
+            Some(c) if c == pair => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Position of a SQL error, as reported by the parser ("... at Line: 2, Column: 8").
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorLocation {
+    /// Line, from 1.
+    pub line: usize,
+    /// Column (in chars), from 1.
+    pub column: usize,
+}
+
+impl ErrorLocation {
+    /// Finds the position of the error in a message, if given.
+    pub fn parse(message: &str) -> Option<Self> {
+        let rest = &message[message.rfind("Line: ")? + "Line: ".len()..];
+        let (line, rest) = rest.split_once(", Column: ")?;
+        let column: String = rest.chars().take_while(char::is_ascii_digit).collect();
+
+        Some(Self {
+            line: line.trim().parse().ok()?,
+            column: column.parse().ok()?,
+        })
+    }
+
+    /// Location of a byte offset of the text.
+    pub fn at_offset(text: &str, offset: usize) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Byte offset of the location in the text.
+    pub fn offset(&self, text: &str) -> usize {
+        let line_start: usize = text
+            .split_inclusive('\n')
+            .take(self.line.saturating_sub(1))
+            .map(str::len)
+            .sum();
+        let line = text[line_start..].split('\n').next().unwrap_or_default();
+        line_start + byte_offset(line, self.column.saturating_sub(1))
+    }
+
+    /// Byte range of the token at the error, or of the last token when it is past the end.
+    pub fn token_range(&self, text: &str) -> Option<Range<usize>> {
+        let offset = self.offset(text);
+
+        let tokens: Vec<Token> = tokenize(text)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+
+        tokens
+            .iter()
+            .find(|token| token.range.end > offset)
+            .or(tokens.last())
+            .map(|token| token.range.clone())
+    }
+}
+
+/// Color of a kind of token, for a dark or a light theme.
+fn token_color(kind: TokenKind, dark_mode: bool, text_color: Color32) -> Color32 {
+    let (dark, light) = match kind {
+        TokenKind::Keyword => ((86, 156, 214), (0, 0, 255)),
+        TokenKind::Function => ((220, 220, 170), (121, 94, 38)),
+        TokenKind::QuotedIdentifier => ((156, 220, 254), (0, 16, 128)),
+        TokenKind::String => ((206, 145, 120), (163, 21, 21)),
+        TokenKind::Number => ((181, 206, 168), (9, 134, 88)),
+        TokenKind::Comment => ((106, 153, 85), (0, 128, 0)),
+        _ => return text_color,
+    };
+
+    let (r, g, b) = if dark_mode { dark } else { light };
+    Color32::from_rgb(r, g, b)
+}
+
+/// Lays out SQL text with syntax highlighting, the error underlined and the brackets
+/// around the cursor (a byte offset) marked.
+pub fn highlight_sql(
+    ui: &Ui,
+    text: &str,
+    error: Option<ErrorLocation>,
+    cursor: Option<usize>,
+) -> LayoutJob {
+    let visuals = ui.visuals();
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let error = error.and_then(|error| error.token_range(text));
+    let brackets = cursor.and_then(|cursor| matching_brackets(text, cursor));
+
+    let mut job = LayoutJob::default();
+    for token in tokenize(text) {
+        let color = token_color(token.kind, visuals.dark_mode, visuals.text_color());
+        let mut format = TextFormat::simple(font_id.clone(), color);
+
+        if error.as_ref() == Some(&token.range) {
+            format.underline = Stroke::new(2.0, visuals.error_fg_color);
+        }
+        if let Some((bracket, pair)) = &brackets {
+            if *bracket == token.range || *pair == token.range {
+                format.background = visuals.selection.bg_fill.gamma_multiply(0.6);
+            }
+        }
+
+        job.append(&text[token.range], 0.0, format);
+    }
+
+    job
+}
+
+/// Renders the SQL editor: line numbers, syntax highlighting, bracket matching and
+/// suggestions of the `completions` for the word being typed.
+///
+/// The error, if any, is underlined until the query changes.
+/// Returns true when the user presses Ctrl+Enter to run the query.
+pub fn render_sql_editor(
+    ui: &mut Ui,
+    query: &mut String,
+    error: &mut Option<ErrorLocation>,
+    completions: &[Completion],
+) -> bool {
+    let id = ui.id().with("sql_editor");
+    let popup_id = id.with("completions");
+    let focused = ui.memory(|memory| memory.has_focus(id));
+
+    // Ctrl+Enter runs the query instead of breaking the line.
+    let run = focused && ui.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::Enter));
+
+    let cursor = TextEdit::load_state(ui.ctx(), id)
+        .and_then(|state| state.cursor.char_range())
+        .map(|range| range.primary.index);
+    let mut popup: CompletionPopup = ui.data(|data| data.get_temp(popup_id)).unwrap_or_default();
+
+    // Keys of the suggestions, handled before the text edit sees them.
+    if let (true, Some(cursor)) = (focused, cursor) {
+        let cursor = byte_offset(query, cursor);
+        if let Some((start, word)) = word_before(query, cursor) {
+            let suggested = suggestions(word, completions);
+            popup.track(start, suggested.len());
+
+            if popup.is_open() && !suggested.is_empty() {
+                let count = suggested.len();
+                let pressed = |key| ui.input_mut(|i| i.consume_key(Modifiers::NONE, key));
+
+                if pressed(Key::ArrowDown) {
+                    popup.selected = (popup.selected + 1) % count;
+                    popup.navigated = true;
+                }
+                if pressed(Key::ArrowUp) {
+                    popup.selected = (popup.selected + count - 1) % count;
+                    popup.navigated = true;
+                }
+                if pressed(Key::Escape) {
+                    popup.dismissed = Some(start);
+                }
+                // Enter picks a suggestion only once the user moved through the list.
+                if pressed(Key::Tab) || (popup.navigated && pressed(Key::Enter)) {
+                    let text = suggested[popup.selected].insert_text();
+                    complete(ui, id, query, start..cursor, &text);
+                }
+            }
+        }
+    }
+
+    let cursor = TextEdit::load_state(ui.ctx(), id)
+        .and_then(|state| state.cursor.char_range())
+        .map(|range| range.primary.index);
+    let location = *error;
+    let mut layouter = |ui: &Ui, text: &str, wrap_width: f32| {
+        let cursor = cursor.map(|index| byte_offset(text, index));
+        let mut job = highlight_sql(ui, text, location, cursor);
+        job.wrap.max_width = wrap_width;
+        ui.fonts(|fonts| fonts.layout_job(job))
+    };
+
+    // Room for the line numbers, drawn once the text is laid out.
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let digits = query.split('\n').count().to_string().len();
+    let gutter = ui.fonts(|fonts| fonts.glyph_width(&font_id, '0')) * (digits as f32 + 1.0);
+
+    let output = ui
+        .horizontal_top(|ui| {
+            ui.add_space(gutter);
+            TextEdit::multiline(query)
+                .id(id)
+                .code_editor()
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .layouter(&mut layouter)
+                .show(ui)
+        })
+        .inner;
+
+    // Number the rows that start a line; wrapped rows are not numbered.
+    let painter = ui.painter();
+    let color = ui.visuals().weak_text_color();
+    let mut number = 1;
+    let mut starts_line = true;
+    for row in &output.galley.rows {
+        if starts_line {
+            let y = output.galley_pos.y + row.rect.top();
+            let position = pos2(output.response.rect.left() - 4.0, y);
+            painter.text(position, Align2::RIGHT_TOP, number, font_id.clone(), color);
+            number += 1;
+        }
+        starts_line = row.ends_with_newline;
+    }
+
+    if output.response.changed() {
+        *error = None;
+    }
+
+    // The suggestions stay shown while the pointer is on them, to be clicked.
+    let hovered = popup
+        .rect
+        .zip(ui.input(|i| i.pointer.hover_pos()))
+        .is_some_and(|(rect, pointer)| rect.contains(pointer));
+    popup.rect = None;
+
+    let cursor = output.state.cursor.char_range().map(|range| range.primary);
+    if let (true, Some(cursor)) = (output.response.has_focus() || hovered, cursor) {
+        let offset = byte_offset(query, cursor.index);
+        if let Some((start, word)) = word_before(query, offset) {
+            let suggested = suggestions(word, completions);
+            popup.track(start, suggested.len());
+
+            if popup.is_open() && !suggested.is_empty() {
+                let position = output.galley_pos
+                    + output
+                        .galley
+                        .pos_from_ccursor(cursor)
+                        .left_bottom()
+                        .to_vec2();
+                let mut clicked: Option<String> = None;
+
+                let area = Area::new(popup_id)
+                    .order(Order::Foreground)
+                    .fixed_pos(position)
+                    .show(ui.ctx(), |ui| {
+                        Frame::popup(ui.style()).show(ui, |ui| {
+                            for (index, completion) in suggested.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    let label = ui.selectable_label(
+                                        index == popup.selected,
+                                        &completion.label,
+                                    );
+                                    ui.weak(completion.kind.label());
+                                    if label.clicked() {
+                                        clicked = Some(completion.insert_text());
+                                    }
+                                });
+                            }
+                        });
+                    });
+                popup.rect = Some(area.response.rect);
+
+                if let Some(text) = clicked {
+                    complete(ui, id, query, start..offset, &text);
+                    ui.memory_mut(|memory| memory.request_focus(id));
+                }
+            }
+        }
+    }
+
+    ui.data_mut(|data| data.insert_temp(popup_id, popup));
+
+    run
+}
+
+/// Replaces the word being typed (a byte range of the query) with a completion,
+/// moving the cursor of the editor after it.
+fn complete(ui: &Ui, id: Id, query: &mut String, word: Range<usize>, text: &str) {
+    let end = word.start + text.len();
+    query.replace_range(word, text);
+
+    let mut state = TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+    let cursor = CCursor::new(query[..end].chars().count());
+    state.cursor.set_char_range(Some(CCursorRange::one(cursor)));
+    state.store(ui.ctx(), id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let text = "SELECT sum(`Valor`), 'it''s' -- total\nFROM t";
+        let tokens: Vec<(TokenKind, &str)> = tokenize(text)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, &text[token.range]))
+            .collect();
+
+        assert_eq!(
+            tokens,
+            [
+                (TokenKind::Keyword, "SELECT"),
+                (TokenKind::Function, "sum"),
+                (TokenKind::Bracket, "("),
+                (TokenKind::QuotedIdentifier, "`Valor`"),
+                (TokenKind::Bracket, ")"),
+                (TokenKind::Operator, ","),
+                (TokenKind::String, "'it''s'"),
+                (TokenKind::Comment, "-- total"),
+                (TokenKind::Keyword, "FROM"),
+                (TokenKind::Identifier, "t"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matching_brackets() {
+        let text = "f(a, g(b), ')')";
+        assert_eq!(matching_brackets(text, 2), Some((1..2, 14..15))); // After "f(".
+        assert_eq!(matching_brackets(text, 15), Some((14..15, 1..2)));
+        assert_eq!(matching_brackets(text, 6), Some((6..7, 8..9))); // Before "(b".
+        assert_eq!(matching_brackets(text, 4), None);
+        assert_eq!(matching_brackets("(a", 1), None);
+    }
+
+    #[test]
+    fn test_split_statements() {
+        let text = "CREATE TABLE t AS SELECT ';' AS a; -- one; two\n;\nSELECT `x;y` FROM t";
+        let statements: Vec<&str> = split_statements(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+        assert_eq!(
+            statements,
+            ["CREATE TABLE t AS SELECT ';' AS a", "\nSELECT `x;y` FROM t"]
+        );
+        assert!(split_statements(" ; -- nothing").is_empty());
+    }
+
+    #[test]
+    fn test_error_location() {
+        let message = "Polars SQL error: sql parser error: Expected: end of statement, \
+                       found: FORM at Line: 2, Column: 3";
+        let location = ErrorLocation::parse(message);
+        assert_eq!(location, Some(ErrorLocation { line: 2, column: 3 }));
+
+        let text = "SELECT *\n  FORM t";
+        let range = location.and_then(|location| location.token_range(text));
+        assert_eq!(range.map(|range| &text[range]), Some("FORM"));
+
+        assert_eq!(ErrorLocation::parse("column not found"), None);
+    }
+}
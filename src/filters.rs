@@ -0,0 +1,585 @@
+use crate::{
+    data::{DataFilters, DataFrameContainer, PendingTask, split_row_numbers},
+    stats::top_values,
+};
+
+use egui::{Color32, ComboBox, Context, Frame, ScrollArea, Stroke, TextEdit, Ui};
+use polars::prelude::*;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Maximum number of distinct values offered as a checklist.
+const MAX_CHECKLIST_VALUES: usize = 50;
+
+/// Condition tested by a column filter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCondition {
+    /// The value, as text, is one of the listed values.
+    OneOf(Vec<String>),
+    /// The value lies between the bounds (inclusive); an empty bound is open.
+    Between {
+        min: Option<String>,
+        max: Option<String>,
+    },
+    /// The text contains the pattern (literally).
+    Contains(String),
+    /// The text starts with the pattern.
+    StartsWith(String),
+    /// The text matches the regular expression.
+    Matches(String),
+    /// The value is null.
+    IsNull,
+    /// The value is not null.
+    IsNotNull,
+}
+
+/// Filter on a single column, built from the column header without writing SQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnFilter {
+    /// Name of the filtered column.
+    pub column: String,
+    /// Condition the rows must satisfy.
+    pub condition: FilterCondition,
+}
+
+impl ColumnFilter {
+    /// Builds the Polars expression selecting the rows that satisfy the filter.
+    pub fn expr(&self, schema: &Schema) -> PolarsResult<Expr> {
+        let Some(dtype) = schema.get(&self.column) else {
+            polars_bail!(ColumnNotFound: "{}", self.column);
+        };
+        let column = col(self.column.as_str());
+        let text = || column.clone().cast(DataType::String);
+
+        let expr = match &self.condition {
+            FilterCondition::OneOf(values) => {
+                let values = Series::new("values".into(), values);
+                text().is_in(lit(values))
+            }
+            FilterCondition::Between { min, max } => {
+                let bound = |value: &str| -> PolarsResult<Expr> {
+                    if dtype.is_primitive_numeric() || dtype.is_decimal() {
+                        let number: f64 = value.trim().parse().map_err(
+                            |_| polars_err!(ComputeError: "'{}' is not a number", value),
+                        )?;
+                        Ok(lit(number))
+                    } else if dtype.is_string() {
+                        Ok(lit(value.to_string()))
+                    } else {
+                        // Dates and other types are compared after parsing the bound.
+                        Ok(lit(value.trim().to_string()).strict_cast(dtype.clone()))
+                    }
+                };
+                let column = match dtype.is_decimal() {
+                    true => column.clone().cast(DataType::Float64),
+                    false => column.clone(),
+                };
+
+                let lower = min.as_deref().map(bound).transpose()?;
+                let upper = max.as_deref().map(bound).transpose()?;
+                match (lower, upper) {
+                    (Some(lower), Some(upper)) => {
+                        column.clone().gt_eq(lower).and(column.lt_eq(upper))
+                    }
+                    (Some(lower), None) => column.gt_eq(lower),
+                    (None, Some(upper)) => column.lt_eq(upper),
+                    (None, None) => lit(true),
+                }
+            }
+            FilterCondition::Contains(pattern) => {
+                text().str().contains_literal(lit(pattern.clone()))
+            }
+            FilterCondition::StartsWith(pattern) => text().str().starts_with(lit(pattern.clone())),
+            FilterCondition::Matches(pattern) => text().str().contains(lit(pattern.clone()), true),
+            FilterCondition::IsNull => column.is_null(),
+            FilterCondition::IsNotNull => column.is_not_null(),
+        };
+
+        Ok(expr)
+    }
+
+    /// Short description shown on the filter chip.
+    pub fn label(&self) -> String {
+        let column = &self.column;
+        match &self.condition {
+            FilterCondition::OneOf(values) => match values.len() {
+                0..=3 => format!("{column} in {{{}}}", values.join(", ")),
+                n => format!("{column} in {n} values"),
+            },
+            FilterCondition::Between { min, max } => match (min, max) {
+                (Some(min), Some(max)) => format!("{min} ≤ {column} ≤ {max}"),
+                (Some(min), None) => format!("{column} ≥ {min}"),
+                (None, Some(max)) => format!("{column} ≤ {max}"),
+                (None, None) => format!("{column} (any value)"),
+            },
+            FilterCondition::Contains(pattern) => format!("{column} contains '{pattern}'"),
+            FilterCondition::StartsWith(pattern) => format!("{column} starts with '{pattern}'"),
+            FilterCondition::Matches(pattern) => format!("{column} matches /{pattern}/"),
+            FilterCondition::IsNull => format!("{column} is null"),
+            FilterCondition::IsNotNull => format!("{column} is not null"),
+        }
+    }
+}
+
+/// Keeps the rows of `lazyframe` satisfying every filter.
+pub fn apply_column_filters(
+    mut lazyframe: LazyFrame,
+    filters: &[ColumnFilter],
+) -> PolarsResult<LazyFrame> {
+    let schema = lazyframe.collect_schema()?;
+
+    for filter in filters {
+        lazyframe = lazyframe.filter(filter.expr(&schema)?);
+    }
+
+    Ok(lazyframe)
+}
+
+impl DataFrameContainer {
+    /// Applies the column filters of `filters` to the unfiltered data, then sorts the result.
+    pub async fn filter(mut self, filters: DataFilters) -> Result<Self, String> {
+        let lazyframe = self.source.as_ref().clone().lazy();
+        let df = apply_column_filters(lazyframe, &filters.column_filters)
+            .and_then(|lazyframe| lazyframe.collect())
+            .map_err(|e| format!("Polars filter error: {}", e))?;
+
+        let (df, row_numbers) = split_row_numbers(df);
+        self.df = Arc::new(df);
+        self.row_numbers = row_numbers;
+        self.filters = filters.clone();
+
+        self.sort(Some(filters)).await
+    }
+
+    /// Renders the active column filters as chips that can be removed.
+    ///
+    /// Returns the new filters when one of them has been removed.
+    pub fn render_filter_chips(&self, ui: &mut Ui) -> Option<DataFilters> {
+        let column_filters = &self.filters.column_filters;
+        if column_filters.is_empty() {
+            return None;
+        }
+
+        let mut remaining: Option<Vec<ColumnFilter>> = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!(
+                "Filters ({} of {} rows):",
+                self.df.height(),
+                self.source.height()
+            ));
+
+            for (index, filter) in column_filters.iter().enumerate() {
+                let chip = Frame::default()
+                    .stroke(Stroke::new(1.0, Color32::GRAY))
+                    .corner_radius(8.0)
+                    .inner_margin(2.0);
+                chip.show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(filter.label());
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Remove filter")
+                            .clicked()
+                        {
+                            let mut filters = column_filters.clone();
+                            filters.remove(index);
+                            remaining = Some(filters);
+                        }
+                    });
+                });
+            }
+
+            if ui.button("Clear filters").clicked() {
+                remaining = Some(Vec::new());
+            }
+        });
+
+        remaining.map(|column_filters| DataFilters {
+            column_filters,
+            ..self.filters.clone()
+        })
+    }
+}
+
+/// Kind of condition being edited in the `FilterEditor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConditionKind {
+    OneOf,
+    Between,
+    Contains,
+    StartsWith,
+    Matches,
+    IsNull,
+    IsNotNull,
+}
+
+impl ConditionKind {
+    /// Name displayed in the UI.
+    fn name(&self) -> &'static str {
+        match self {
+            ConditionKind::OneOf => "Values",
+            ConditionKind::Between => "Range",
+            ConditionKind::Contains => "Contains",
+            ConditionKind::StartsWith => "Starts with",
+            ConditionKind::Matches => "Regex",
+            ConditionKind::IsNull => "Is null",
+            ConditionKind::IsNotNull => "Is not null",
+        }
+    }
+
+    /// Kinds of condition that make sense for a column type.
+    fn available(dtype: &DataType) -> Vec<ConditionKind> {
+        let mut kinds = vec![ConditionKind::OneOf];
+        if dtype.is_primitive_numeric() || dtype.is_decimal() || dtype.is_temporal() {
+            kinds.push(ConditionKind::Between);
+        }
+        if dtype.is_string() || dtype.is_categorical() {
+            kinds.extend([
+                ConditionKind::Contains,
+                ConditionKind::StartsWith,
+                ConditionKind::Matches,
+            ]);
+        }
+        kinds.extend([ConditionKind::IsNull, ConditionKind::IsNotNull]);
+        kinds
+    }
+}
+
+/// Window editing the filter of one column, opened from the column header.
+pub struct FilterEditor {
+    /// Data whose column is filtered.
+    data: DataFrameContainer,
+    /// Name of the filtered column.
+    column: String,
+    /// Type of the filtered column.
+    dtype: DataType,
+    /// Kind of condition being edited.
+    kind: ConditionKind,
+    /// Distinct values of the column with their counts, as text.
+    values: PendingTask<Vec<(String, usize)>>,
+    /// Values checked in the checklist.
+    selected: Vec<String>,
+    /// Lower bound of a range.
+    min: String,
+    /// Upper bound of a range.
+    max: String,
+    /// Pattern of a text condition.
+    pattern: String,
+    /// Whether the window is still open.
+    pub open: bool,
+}
+
+impl FilterEditor {
+    /// Opens the editor, loading the current filter of the column and its distinct values.
+    pub fn new(runtime: &Handle, ctx: &Context, data: DataFrameContainer, column: String) -> Self {
+        let dtype = data
+            .source
+            .schema()
+            .get(&column)
+            .cloned()
+            .unwrap_or(DataType::Null);
+
+        let future = distinct_values(data.source.clone(), column.clone());
+        let values = PendingTask::spawn(runtime, ctx, future);
+
+        let default_kind = match ConditionKind::available(&dtype).contains(&ConditionKind::Between)
+        {
+            true => ConditionKind::Between,
+            false => ConditionKind::OneOf,
+        };
+
+        let mut editor = Self {
+            data,
+            column,
+            dtype,
+            kind: default_kind,
+            values,
+            selected: Vec::new(),
+            min: String::new(),
+            max: String::new(),
+            pattern: String::new(),
+            open: true,
+        };
+
+        // Start from the current filter of the column, if any.
+        let current = (editor.data.filters.column_filters.iter())
+            .find(|filter| filter.column == editor.column)
+            .map(|filter| filter.condition.clone());
+        match current {
+            Some(FilterCondition::OneOf(values)) => {
+                editor.kind = ConditionKind::OneOf;
+                editor.selected = values;
+            }
+            Some(FilterCondition::Between { min, max }) => {
+                editor.kind = ConditionKind::Between;
+                editor.min = min.unwrap_or_default();
+                editor.max = max.unwrap_or_default();
+            }
+            Some(FilterCondition::Contains(pattern)) => {
+                editor.kind = ConditionKind::Contains;
+                editor.pattern = pattern;
+            }
+            Some(FilterCondition::StartsWith(pattern)) => {
+                editor.kind = ConditionKind::StartsWith;
+                editor.pattern = pattern;
+            }
+            Some(FilterCondition::Matches(pattern)) => {
+                editor.kind = ConditionKind::Matches;
+                editor.pattern = pattern;
+            }
+            Some(FilterCondition::IsNull) => editor.kind = ConditionKind::IsNull,
+            Some(FilterCondition::IsNotNull) => editor.kind = ConditionKind::IsNotNull,
+            None => {}
+        }
+
+        editor
+    }
+
+    /// Shows the editor window.
+    ///
+    /// Returns the data to filter and its new filters once the user applies them.
+    pub fn show(&mut self, ctx: &Context) -> Option<(DataFrameContainer, DataFilters)> {
+        let mut condition: Option<Option<FilterCondition>> = None;
+        let mut open = self.open;
+
+        egui::Window::new(format!("Filter: {}", self.column))
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                ComboBox::from_label("Condition")
+                    .selected_text(self.kind.name())
+                    .show_ui(ui, |ui| {
+                        for kind in ConditionKind::available(&self.dtype) {
+                            ui.selectable_value(&mut self.kind, kind, kind.name());
+                        }
+                    });
+                ui.separator();
+
+                match self.kind {
+                    ConditionKind::OneOf => self.render_checklist(ui),
+                    ConditionKind::Between => {
+                        let hint = match self.dtype.is_temporal() {
+                            true => "YYYY-MM-DD",
+                            false => "",
+                        };
+                        ui.horizontal(|ui| {
+                            ui.label("From:");
+                            ui.add(TextEdit::singleline(&mut self.min).hint_text(hint));
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("To:");
+                            ui.add(TextEdit::singleline(&mut self.max).hint_text(hint));
+                        });
+                    }
+                    ConditionKind::Contains
+                    | ConditionKind::StartsWith
+                    | ConditionKind::Matches => {
+                        ui.text_edit_singleline(&mut self.pattern);
+                    }
+                    ConditionKind::IsNull | ConditionKind::IsNotNull => {}
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Apply").clicked() {
+                        condition = Some(Some(self.condition()));
+                    }
+                    if ui.button("Remove filter").clicked() {
+                        condition = Some(None);
+                    }
+                });
+            });
+
+        self.open = open;
+
+        // Replace the previous filter of the column, if any.
+        let condition = condition?;
+        self.open = false;
+
+        let mut filters = self.data.filters.clone();
+        filters
+            .column_filters
+            .retain(|filter| filter.column != self.column);
+        if let Some(condition) = condition {
+            filters.column_filters.push(ColumnFilter {
+                column: self.column.clone(),
+                condition,
+            });
+        }
+
+        Some((self.data.clone(), filters))
+    }
+
+    /// Renders the checklist of distinct values.
+    fn render_checklist(&mut self, ui: &mut Ui) {
+        match self.values.poll() {
+            None => {
+                ui.spinner(); // Distinct values are still being computed.
+            }
+            Some(Err(message)) => {
+                ui.label(format!("Error: {}", message));
+            }
+            Some(Ok(values)) if values.len() > MAX_CHECKLIST_VALUES => {
+                ui.label(format!(
+                    "More than {MAX_CHECKLIST_VALUES} distinct values: use a range or a text condition."
+                ));
+            }
+            Some(Ok(values)) => {
+                ui.horizontal(|ui| {
+                    if ui.small_button("All").clicked() {
+                        self.selected = values.iter().map(|(value, _)| value.clone()).collect();
+                    }
+                    if ui.small_button("None").clicked() {
+                        self.selected.clear();
+                    }
+                });
+
+                ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (value, count) in values {
+                        let mut checked = self.selected.contains(value);
+                        if ui
+                            .checkbox(&mut checked, format!("{value} ({count})"))
+                            .changed()
+                        {
+                            match checked {
+                                true => self.selected.push(value.clone()),
+                                false => self.selected.retain(|selected| selected != value),
+                            }
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Builds the condition from the edited values.
+    fn condition(&self) -> FilterCondition {
+        let bound = |text: &str| Some(text.trim().to_string()).filter(|text| !text.is_empty());
+
+        match self.kind {
+            ConditionKind::OneOf => FilterCondition::OneOf(self.selected.clone()),
+            ConditionKind::Between => FilterCondition::Between {
+                min: bound(&self.min),
+                max: bound(&self.max),
+            },
+            ConditionKind::Contains => FilterCondition::Contains(self.pattern.clone()),
+            ConditionKind::StartsWith => FilterCondition::StartsWith(self.pattern.clone()),
+            ConditionKind::Matches => FilterCondition::Matches(self.pattern.clone()),
+            ConditionKind::IsNull => FilterCondition::IsNull,
+            ConditionKind::IsNotNull => FilterCondition::IsNotNull,
+        }
+    }
+}
+
+/// Lists the distinct non-null values of a column, as text, with their counts.
+///
+/// One value more than the checklist limit is returned, so that the UI knows when it is exceeded.
+async fn distinct_values(
+    df: Arc<DataFrame>,
+    column: String,
+) -> Result<Vec<(String, usize)>, String> {
+    let lazyframe = df
+        .as_ref()
+        .clone()
+        .lazy()
+        .select([col(column.as_str()).cast(DataType::String)]);
+
+    top_values(lazyframe, &column, MAX_CHECKLIST_VALUES + 1)
+        .map_err(|e| format!("Polars error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(column: &str, condition: FilterCondition) -> ColumnFilter {
+        ColumnFilter {
+            column: column.to_string(),
+            condition,
+        }
+    }
+
+    fn apply(df: &DataFrame, filters: &[ColumnFilter]) -> PolarsResult<DataFrame> {
+        apply_column_filters(df.clone().lazy(), filters)?.collect()
+    }
+
+    fn sample() -> PolarsResult<DataFrame> {
+        df![
+            "name" => [Some("Entrada"), Some("Saída"), None, Some("Estorno")],
+            "value" => [Some(1.5), Some(10.0), Some(3.0), None],
+        ]
+    }
+
+    #[test]
+    fn test_one_of_and_range() -> PolarsResult<()> {
+        let df = sample()?;
+
+        let names = filter(
+            "name",
+            FilterCondition::OneOf(vec!["Entrada".to_string(), "Saída".to_string()]),
+        );
+        assert_eq!(apply(&df, std::slice::from_ref(&names))?.height(), 2);
+
+        let range = filter(
+            "value",
+            FilterCondition::Between {
+                min: Some("2".to_string()),
+                max: None,
+            },
+        );
+        let filtered = apply(&df, &[names, range])?;
+        assert_eq!(filtered.height(), 1);
+        assert_eq!(filtered.column("value")?.f64()?.get(0), Some(10.0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_and_null_conditions() -> PolarsResult<()> {
+        let df = sample()?;
+        let count = |condition| apply(&df, &[filter("name", condition)]).map(|df| df.height());
+
+        assert_eq!(count(FilterCondition::Contains("E".to_string()))?, 2);
+        assert_eq!(count(FilterCondition::StartsWith("Sa".to_string()))?, 1);
+        assert_eq!(count(FilterCondition::Matches("^E.*o$".to_string()))?, 1);
+        assert_eq!(count(FilterCondition::IsNull)?, 1);
+        assert_eq!(count(FilterCondition::IsNotNull)?, 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_range() -> PolarsResult<()> {
+        let df = df!["day" => ["2024-01-10", "2024-02-10", "2024-03-10"]]?
+            .lazy()
+            .select([col("day").cast(DataType::Date)])
+            .collect()?;
+
+        let range = filter(
+            "day",
+            FilterCondition::Between {
+                min: Some("2024-02-01".to_string()),
+                max: Some("2024-03-10".to_string()),
+            },
+        );
+        assert_eq!(apply(&df, &[range])?.height(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_number_is_an_error() -> PolarsResult<()> {
+        let df = sample()?;
+        let range = filter(
+            "value",
+            FilterCondition::Between {
+                min: Some("abc".to_string()),
+                max: None,
+            },
+        );
+        assert!(apply(&df, &[range]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,555 @@
+use chrono::{
+    DateTime, NaiveDate, NaiveDateTime,
+    format::{Item, StrftimeItems},
+};
+use egui::{Align, ComboBox, Direction, DragValue, Grid, Layout, TextEdit, Ui};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Group of data types a formatting rule can apply to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DtypeGroup {
+    Float,
+    Integer,
+    Date,
+    Datetime,
+    Text,
+    Boolean,
+}
+
+impl DtypeGroup {
+    /// Every group, in the order offered by the editor.
+    pub const ALL: [DtypeGroup; 6] = [
+        DtypeGroup::Float,
+        DtypeGroup::Integer,
+        DtypeGroup::Date,
+        DtypeGroup::Datetime,
+        DtypeGroup::Text,
+        DtypeGroup::Boolean,
+    ];
+
+    /// Checks whether a data type belongs to the group.
+    pub fn contains(self, dtype: &DataType) -> bool {
+        match self {
+            DtypeGroup::Float => dtype.is_float(),
+            DtypeGroup::Integer => dtype.is_integer(),
+            DtypeGroup::Date => dtype.is_date(),
+            DtypeGroup::Datetime => matches!(dtype, DataType::Datetime(..)),
+            DtypeGroup::Text => dtype.is_string(),
+            DtypeGroup::Boolean => dtype.is_bool(),
+        }
+    }
+}
+
+/// Horizontal alignment of the cells of a column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl CellAlign {
+    /// Layout of a cell with this alignment.
+    pub fn layout(self) -> Layout {
+        match self {
+            CellAlign::Left => Layout::left_to_right(Align::Center),
+            CellAlign::Center => Layout::centered_and_justified(Direction::LeftToRight),
+            CellAlign::Right => Layout::right_to_left(Align::Center),
+        }
+    }
+}
+
+/// Formatting of the columns matched by name and type; unset options are left to the next rules.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatRule {
+    /// Text contained in the names of the columns, ignoring case; any name when empty.
+    pub column: String,
+    /// Group of data types of the columns; any type when None.
+    pub dtype: Option<DtypeGroup>,
+    /// Decimal places of floating point numbers.
+    pub decimals: Option<usize>,
+    /// Group the digits of numbers by thousands.
+    pub thousands: Option<bool>,
+    /// Write numbers with a decimal comma (and `.` between thousands), as in pt-BR.
+    pub decimal_comma: Option<bool>,
+    /// `strftime` format of dates and datetimes, such as `%d/%m/%Y`.
+    pub date_format: Option<String>,
+    pub align: Option<CellAlign>,
+    /// Text shown for null values.
+    pub null: Option<String>,
+}
+
+impl FormatRule {
+    /// Creates a rule leaving every option to the next rules.
+    pub fn new(column: &str, dtype: Option<DtypeGroup>) -> Self {
+        Self {
+            column: column.to_string(),
+            dtype,
+            decimals: None,
+            thousands: None,
+            decimal_comma: None,
+            date_format: None,
+            align: None,
+            null: None,
+        }
+    }
+
+    /// Checks whether the rule applies to a column.
+    pub fn matches(&self, name: &str, dtype: &DataType) -> bool {
+        name.to_lowercase().contains(&self.column.to_lowercase())
+            && self.dtype.is_none_or(|group| group.contains(dtype))
+    }
+}
+
+/// Formatting of one column, resolved from the rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CellFormat {
+    pub decimals: Option<usize>,
+    pub thousands: bool,
+    pub decimal_comma: bool,
+    pub date_format: Option<String>,
+    pub align: CellAlign,
+    pub null: String,
+}
+
+impl CellFormat {
+    /// Formats the value of a cell.
+    pub fn format(&self, value: AnyValue) -> String {
+        match value {
+            AnyValue::Null => self.null.clone(),
+            AnyValue::String(text) => text.to_string(),
+            AnyValue::Float32(float) => self.format_number(float as f64),
+            AnyValue::Float64(float) => self.format_number(float),
+            value if value.is_integer() => self.localize(value.to_string()),
+            AnyValue::Date(days) => match &self.date_format {
+                Some(format) => format_date(days, format).unwrap_or_else(|| value.to_string()),
+                None => value.to_string(),
+            },
+            AnyValue::Datetime(timestamp, unit, _) => match &self.date_format {
+                Some(format) => {
+                    format_datetime(timestamp, unit, format).unwrap_or_else(|| value.to_string())
+                }
+                None => value.to_string(),
+            },
+            value => value.to_string(),
+        }
+    }
+
+    /// Formats the values of a column, into a column of the texts shown by the table.
+    pub fn format_column(&self, column: &Column) -> Column {
+        let values: StringChunked = column
+            .as_materialized_series()
+            .rechunk()
+            .iter()
+            .map(|value| Some(self.format(value)))
+            .collect();
+        values.with_name(column.name().clone()).into_column()
+    }
+
+    /// Expression giving the texts shown by the table for the values of a column.
+    pub fn expr(&self, column: &str) -> Expr {
+        let format = self.clone();
+        col(column).map(
+            move |column| Ok(Some(format.format_column(&column))),
+            GetOutput::from_type(DataType::String),
+        )
+    }
+
+    /// Formats a floating point number with the configured decimal places.
+    fn format_number(&self, float: f64) -> String {
+        let text = match self.decimals {
+            Some(decimals) => format!("{float:.decimals$}"),
+            None => float.to_string(),
+        };
+        self.localize(text)
+    }
+
+    /// Applies the thousands separator and the decimal comma to a formatted number.
+    fn localize(&self, text: String) -> String {
+        if !self.thousands && !self.decimal_comma {
+            return text;
+        }
+
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text.as_str()),
+        };
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (digits, None),
+        };
+        if integer.is_empty() || !integer.chars().all(|c| c.is_ascii_digit()) {
+            return text; // NaN, inf or scientific notation.
+        }
+
+        let (separator, point) = match self.decimal_comma {
+            true => ('.', ','),
+            false => (',', '.'),
+        };
+
+        let mut localized = sign.to_string();
+        for (index, digit) in integer.chars().enumerate() {
+            if self.thousands && index > 0 && (integer.len() - index) % 3 == 0 {
+                localized.push(separator);
+            }
+            localized.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            localized.push(point);
+            localized.push_str(fraction);
+        }
+
+        localized
+    }
+}
+
+/// Formats the days since 1970-01-01 with a `strftime` format; None when the format is invalid.
+fn format_date(days: i32, format: &str) -> Option<String> {
+    let date = NaiveDate::from_num_days_from_ce_opt(days.checked_add(719_163)?)?;
+    format_with(format, |items, text| {
+        write!(text, "{}", date.format_with_items(items))
+    })
+}
+
+/// Formats a timestamp with a `strftime` format; None when the format is invalid.
+fn format_datetime(timestamp: i64, unit: TimeUnit, format: &str) -> Option<String> {
+    let datetime: NaiveDateTime = match unit {
+        TimeUnit::Nanoseconds => DateTime::from_timestamp_nanos(timestamp),
+        TimeUnit::Microseconds => DateTime::from_timestamp_micros(timestamp)?,
+        TimeUnit::Milliseconds => DateTime::from_timestamp_millis(timestamp)?,
+    }
+    .naive_utc();
+
+    format_with(format, |items, text| {
+        write!(text, "{}", datetime.format_with_items(items))
+    })
+}
+
+/// Parses a `strftime` format and writes a value with it.
+fn format_with(
+    format: &str,
+    write: impl FnOnce(StrftimeItems, &mut String) -> std::fmt::Result,
+) -> Option<String> {
+    let items = StrftimeItems::new(format);
+    if items.clone().any(|item| item == Item::Error) {
+        return None;
+    }
+
+    let mut text = String::new();
+    write(items, &mut text).ok()?;
+    Some(text)
+}
+
+/// Formatting rules of the table, applied in order: the first rule setting an option wins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatSettings {
+    pub rules: Vec<FormatRule>,
+}
+
+impl Default for FormatSettings {
+    /// Two decimals for numbers, four for tax rates ("Alíquota"), and centered integers and dates.
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                FormatRule {
+                    decimals: Some(4),
+                    align: Some(CellAlign::Center),
+                    ..FormatRule::new("Alíquota", Some(DtypeGroup::Float))
+                },
+                FormatRule {
+                    decimals: Some(2),
+                    align: Some(CellAlign::Right),
+                    ..FormatRule::new("", Some(DtypeGroup::Float))
+                },
+                FormatRule {
+                    align: Some(CellAlign::Center),
+                    ..FormatRule::new("", Some(DtypeGroup::Integer))
+                },
+                FormatRule {
+                    align: Some(CellAlign::Center),
+                    ..FormatRule::new("", Some(DtypeGroup::Date))
+                },
+            ],
+        }
+    }
+}
+
+impl FormatSettings {
+    /// Resolves the formatting of a column from the rules matching it.
+    pub fn resolve(&self, name: &str, dtype: &DataType) -> CellFormat {
+        let rules: Vec<&FormatRule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.matches(name, dtype))
+            .collect();
+
+        CellFormat {
+            decimals: rules.iter().find_map(|rule| rule.decimals),
+            thousands: rules
+                .iter()
+                .find_map(|rule| rule.thousands)
+                .unwrap_or(false),
+            decimal_comma: rules
+                .iter()
+                .find_map(|rule| rule.decimal_comma)
+                .unwrap_or(false),
+            date_format: rules.iter().find_map(|rule| rule.date_format.clone()),
+            align: rules.iter().find_map(|rule| rule.align).unwrap_or_default(),
+            null: rules
+                .iter()
+                .find_map(|rule| rule.null.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Renders the rules editor.
+    ///
+    /// Returns true when the rules changed.
+    pub fn render_editor(&mut self, ui: &mut Ui) -> bool {
+        let before = self.clone();
+        let mut moved: Option<(usize, usize)> = None;
+        let mut removed: Option<usize> = None;
+
+        ui.label(
+            "Rules apply in order: for each option, the first matching rule that sets it wins.",
+        );
+
+        Grid::new("format_rules")
+            .num_columns(9)
+            .striped(true)
+            .show(ui, |ui| {
+                for header in [
+                    "Name has",
+                    "Type",
+                    "Decimals",
+                    "Thousands",
+                    "Decimal comma",
+                    "Date format",
+                    "Align",
+                    "Null as",
+                    "",
+                ] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+
+                let count = self.rules.len();
+                for (index, rule) in self.rules.iter_mut().enumerate() {
+                    ui.add(
+                        TextEdit::singleline(&mut rule.column)
+                            .hint_text("any")
+                            .desired_width(90.0),
+                    );
+                    ui.push_id(("dtype", index), |ui| dtype_editor(ui, &mut rule.dtype));
+                    ui.push_id(("decimals", index), |ui| {
+                        optional(ui, &mut rule.decimals, 2, |ui, decimals| {
+                            ui.add(DragValue::new(decimals).range(0..=10));
+                        });
+                    });
+                    ui.push_id(("thousands", index), |ui| yes_no(ui, &mut rule.thousands));
+                    ui.push_id(("comma", index), |ui| yes_no(ui, &mut rule.decimal_comma));
+                    ui.push_id(("date", index), |ui| {
+                        optional(
+                            ui,
+                            &mut rule.date_format,
+                            "%d/%m/%Y".to_string(),
+                            |ui, text| {
+                                ui.add(TextEdit::singleline(text).desired_width(90.0));
+                            },
+                        );
+                    });
+                    ui.push_id(("align", index), |ui| {
+                        optional(ui, &mut rule.align, CellAlign::Left, |ui, align| {
+                            ComboBox::from_id_salt("align")
+                                .selected_text(format!("{align:?}"))
+                                .show_ui(ui, |ui| {
+                                    for option in
+                                        [CellAlign::Left, CellAlign::Center, CellAlign::Right]
+                                    {
+                                        ui.selectable_value(align, option, format!("{option:?}"));
+                                    }
+                                });
+                        });
+                    });
+                    ui.push_id(("null", index), |ui| {
+                        optional(ui, &mut rule.null, "null".to_string(), |ui, text| {
+                            ui.add(TextEdit::singleline(text).desired_width(50.0));
+                        });
+                    });
+
+                    ui.horizontal(|ui| {
+                        if ui.add_enabled(index > 0, egui::Button::new("⏶")).clicked() {
+                            moved = Some((index, index - 1));
+                        }
+                        if ui
+                            .add_enabled(index + 1 < count, egui::Button::new("⏷"))
+                            .clicked()
+                        {
+                            moved = Some((index, index + 1));
+                        }
+                        if ui.button("🗑").on_hover_text("Remove rule").clicked() {
+                            removed = Some(index);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        if let Some((from, to)) = moved {
+            self.rules.swap(from, to);
+        }
+        if let Some(index) = removed {
+            self.rules.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add rule").clicked() {
+                self.rules.push(FormatRule::new("", None));
+            }
+            if ui.button("Reset to defaults").clicked() {
+                *self = FormatSettings::default();
+            }
+        });
+
+        *self != before
+    }
+}
+
+/// Edits the group of data types of a rule.
+fn dtype_editor(ui: &mut Ui, dtype: &mut Option<DtypeGroup>) {
+    let text = match dtype {
+        Some(group) => format!("{group:?}"),
+        None => "any".to_string(),
+    };
+    ComboBox::from_id_salt("dtype")
+        .selected_text(text)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(dtype, None, "any");
+            for group in DtypeGroup::ALL {
+                ui.selectable_value(dtype, Some(group), format!("{group:?}"));
+            }
+        });
+}
+
+/// Edits an option that may be left to the next rules: a checkbox enables it.
+fn optional<T>(ui: &mut Ui, value: &mut Option<T>, default: T, edit: impl FnOnce(&mut Ui, &mut T)) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        ui.checkbox(&mut enabled, "");
+        match (enabled, value.as_mut()) {
+            (true, Some(value)) => edit(ui, value),
+            (true, None) => *value = Some(default),
+            (false, _) => *value = None,
+        }
+    });
+}
+
+/// Edits a yes/no option that may be left to the next rules.
+fn yes_no(ui: &mut Ui, value: &mut Option<bool>) {
+    let text = match value {
+        None => "—",
+        Some(true) => "yes",
+        Some(false) => "no",
+    };
+    ComboBox::from_id_salt("yes_no")
+        .selected_text(text)
+        .width(50.0)
+        .show_ui(ui, |ui| {
+            ui.selectable_value(value, None, "—");
+            ui.selectable_value(value, Some(true), "yes");
+            ui.selectable_value(value, Some(false), "no");
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules() {
+        let formats = FormatSettings::default();
+
+        let rate = formats.resolve("Alíquota de PIS", &DataType::Float64);
+        assert_eq!(rate.decimals, Some(4));
+        assert_eq!(rate.align, CellAlign::Center);
+        assert_eq!(rate.format(AnyValue::Float64(1.65)), "1.6500");
+
+        let value = formats.resolve("Valor", &DataType::Float64);
+        assert_eq!(value.align, CellAlign::Right);
+        assert_eq!(value.format(AnyValue::Float64(2.0 / 3.0)), "0.67");
+        assert_eq!(value.format(AnyValue::Null), "");
+
+        let text = formats.resolve("Alíquota", &DataType::String);
+        assert_eq!(text, CellFormat::default());
+    }
+
+    #[test]
+    fn test_rules_in_order() {
+        let mut formats = FormatSettings::default();
+        formats.rules.insert(
+            0,
+            FormatRule {
+                thousands: Some(true),
+                decimal_comma: Some(true),
+                null: Some("—".to_string()),
+                ..FormatRule::new("valor", None)
+            },
+        );
+
+        let value = formats.resolve("Valor Total", &DataType::Float64);
+        assert_eq!(value.decimals, Some(2)); // Left to the Float rule.
+        assert_eq!(
+            value.format(AnyValue::Float64(-1234567.891)),
+            "-1.234.567,89"
+        );
+        assert_eq!(value.format(AnyValue::Float64(999.5)), "999,50");
+        assert_eq!(value.format(AnyValue::Float64(f64::NAN)), "NaN");
+        assert_eq!(value.format(AnyValue::Null), "—");
+
+        let count = formats.resolve("Valor Count", &DataType::Int64);
+        assert_eq!(count.format(AnyValue::Int64(1_000_000)), "1.000.000");
+    }
+
+    #[test]
+    fn test_date_formats() {
+        let format = CellFormat {
+            date_format: Some("%d/%m/%Y".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(format.format(AnyValue::Date(19_723)), "01/01/2024");
+
+        let datetime = AnyValue::Datetime(1_704_112_496_000, TimeUnit::Milliseconds, None);
+        let format = CellFormat {
+            date_format: Some("%d/%m/%Y %H:%M".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(format.format(datetime), "01/01/2024 12:34");
+
+        // An invalid format falls back to the default one.
+        let format = CellFormat {
+            date_format: Some("%Q".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(format.format(AnyValue::Date(19_723)), "2024-01-01");
+    }
+
+    #[test]
+    fn test_format_column() -> PolarsResult<()> {
+        let df = df!["Valor" => [Some(1234.5), None]]?;
+        let format = CellFormat {
+            decimals: Some(2),
+            decimal_comma: true,
+            null: "—".to_string(),
+            ..Default::default()
+        };
+
+        let shown = df.lazy().select([format.expr("Valor")]).collect()?;
+        let texts: Vec<Option<&str>> = shown["Valor"].str()?.iter().collect();
+        assert_eq!(texts, [Some("1234,50"), Some("—")]);
+
+        Ok(())
+    }
+}
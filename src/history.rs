@@ -0,0 +1,322 @@
+use crate::{DataFilters, Preview, fold, tabs::file_name};
+
+use egui::{Color32, RichText, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, time::Duration};
+
+/// Name of the history file, in the storage directory of the application.
+const HISTORY_FILE: &str = "history.json";
+
+/// Maximum number of queries kept in the history: the oldest are dropped.
+const MAX_ENTRIES: usize = 500;
+
+/// A query executed by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub sql: String,
+    /// File queried, registered as `table_name`.
+    pub filename: String,
+    pub table_name: String,
+    #[serde(default)]
+    pub csv_delimiter: Option<String>,
+    /// Local time the query started, as "YYYY-MM-DD HH:MM:SS".
+    pub timestamp: String,
+    pub duration_ms: u64,
+    /// Number of rows of the result, if the query succeeded.
+    pub rows: Option<usize>,
+    /// Error of the query, if it failed.
+    pub error: Option<String>,
+    /// Rows of the file the query ran on, if it was a preview.
+    #[serde(default)]
+    pub preview: Option<Preview>,
+}
+
+impl HistoryEntry {
+    /// Starts the entry of a query about to run with these filters.
+    pub fn new(filters: &DataFilters) -> Self {
+        Self {
+            sql: filters.query.clone().unwrap_or_default(),
+            filename: filters.filename.clone().unwrap_or_default(),
+            table_name: filters.table_name.clone().unwrap_or_default(),
+            csv_delimiter: filters.csv_delimiter.clone(),
+            timestamp: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            duration_ms: 0,
+            rows: None,
+            error: None,
+            preview: filters.preview,
+        }
+    }
+
+    /// Completes the entry with the outcome of the query: its number of rows or its error.
+    pub fn finish(mut self, elapsed: Duration, outcome: Result<usize, &str>) -> Self {
+        self.duration_ms = elapsed.as_millis() as u64;
+        match outcome {
+            Ok(rows) => self.rows = Some(rows),
+            Err(error) => self.error = Some(error.to_string()),
+        }
+        self
+    }
+
+    /// Whether the query, its file or its error contain the searched text,
+    /// ignoring case and accents.
+    pub fn matches(&self, search: &str) -> bool {
+        let key = fold(search.trim());
+        [Some(&self.sql), Some(&self.filename), self.error.as_ref()]
+            .into_iter()
+            .flatten()
+            .any(|text| fold(text).contains(&key))
+    }
+}
+
+/// A line of the difference between two queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    /// Line only in the old query.
+    Removed(&'a str),
+    /// Line only in the new query.
+    Added(&'a str),
+}
+
+/// Line by line difference between two queries, from their longest common subsequence.
+pub fn line_diff<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the longest common subsequence of old[i..] and new[j..].
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Action requested from the query history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HistoryAction {
+    /// Run this query again, on its file.
+    Rerun(HistoryEntry),
+    /// Add this query to the saved queries.
+    Pin(HistoryEntry),
+    /// Forget every query.
+    Clear,
+}
+
+/// Queries executed by the user, oldest first, stored in a JSON file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryHistory {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl QueryHistory {
+    /// Path of the history file, in the storage directory of the application.
+    pub fn path() -> Option<PathBuf> {
+        eframe::storage_dir("PolarsView").map(|dir| dir.join(HISTORY_FILE))
+    }
+
+    /// Loads the history file, or an empty history if there is none yet.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path().filter(|path| path.exists()) else {
+            return Ok(Self::default());
+        };
+        let json =
+            fs::read_to_string(&path).map_err(|e| format!("Error reading {:?}: {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid query history {:?}: {}", path, e))
+    }
+
+    /// Saves the history file.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No directory to save the query history.")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Error creating {:?}: {}", dir, e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json).map_err(|e| format!("Error writing {:?}: {}", path, e))
+    }
+
+    /// Adds an executed query, dropping the oldest ones beyond the maximum.
+    pub fn record(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+        let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..excess);
+    }
+
+    /// Renders the history, newest first, filtered by a search box.
+    ///
+    /// `current` is the query of the editor, compared with an entry by its "Diff" button.
+    pub fn render(&self, ui: &mut Ui, current: &str) -> Option<HistoryAction> {
+        let mut action: Option<HistoryAction> = None;
+        let search_id = ui.id().with("history_search");
+        let diff_id = ui.id().with("history_diff");
+        let mut search: String = ui.data(|data| data.get_temp(search_id)).unwrap_or_default();
+        let mut diff: Option<usize> = ui.data(|data| data.get_temp(diff_id)).flatten();
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut search)
+                    .hint_text("Search queries, files and errors")
+                    .desired_width(ui.available_width() - 50.0),
+            );
+            let clear = ui.add_enabled(!self.entries.is_empty(), egui::Button::new("Clear"));
+            if clear.on_hover_text("Forget every query").clicked() {
+                action = Some(HistoryAction::Clear);
+            }
+        });
+        ui.separator();
+
+        let entries = self.entries.iter().enumerate().rev();
+        for (index, entry) in entries.filter(|(_, entry)| entry.matches(&search)) {
+            ui.push_id(index, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let outcome = match (&entry.error, entry.rows) {
+                        (Some(_), _) => RichText::new("✖").color(ui.visuals().error_fg_color),
+                        (None, rows) => RichText::new(format!("✔ {} rows", rows.unwrap_or(0))),
+                    };
+                    ui.label(outcome);
+                    if let Some(preview) = entry.preview {
+                        ui.colored_label(ui.visuals().warn_fg_color, "Preview")
+                            .on_hover_text(preview.label());
+                    }
+                    ui.weak(format!(
+                        "{} · {} ms · {}",
+                        entry.timestamp,
+                        entry.duration_ms,
+                        file_name(&entry.filename)
+                    ))
+                    .on_hover_text(&entry.filename);
+                });
+
+                ui.label(RichText::new(&entry.sql).monospace());
+                if let Some(error) = &entry.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("▶ Run").on_hover_text("Run again").clicked() {
+                        action = Some(HistoryAction::Rerun(entry.clone()));
+                    }
+                    if ui
+                        .button("📌 Pin")
+                        .on_hover_text("Add to the saved queries")
+                        .clicked()
+                    {
+                        action = Some(HistoryAction::Pin(entry.clone()));
+                    }
+                    let diffing = diff == Some(index);
+                    if ui
+                        .selectable_label(diffing, "Diff")
+                        .on_hover_text("Compare with the query of the editor")
+                        .clicked()
+                    {
+                        diff = (!diffing).then_some(index);
+                    }
+                });
+
+                if diff == Some(index) {
+                    render_diff(ui, &entry.sql, current);
+                }
+            });
+            ui.separator();
+        }
+
+        ui.data_mut(|data| {
+            data.insert_temp(search_id, search);
+            data.insert_temp(diff_id, diff);
+        });
+
+        action
+    }
+}
+
+/// Shows the lines removed from the old query in red and those added by the new one in green.
+fn render_diff(ui: &mut Ui, old: &str, new: &str) {
+    let diff = line_diff(old, new);
+    if diff.iter().all(|line| matches!(line, DiffLine::Same(_))) {
+        ui.weak("Same as the query of the editor.");
+        return;
+    }
+
+    for line in diff {
+        let (text, color) = match line {
+            DiffLine::Same(text) => (format!("  {text}"), ui.visuals().weak_text_color()),
+            DiffLine::Removed(text) => (format!("- {text}"), Color32::from_rgb(214, 39, 40)),
+            DiffLine::Added(text) => (format!("+ {text}"), Color32::from_rgb(44, 160, 44)),
+        };
+        ui.label(RichText::new(text).monospace().color(color));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let old = "SELECT *\nFROM notas\nWHERE valor > 0";
+        let new = "SELECT *\nFROM notas\nWHERE valor > 10\nLIMIT 5";
+        assert_eq!(
+            line_diff(old, new),
+            [
+                DiffLine::Same("SELECT *"),
+                DiffLine::Same("FROM notas"),
+                DiffLine::Removed("WHERE valor > 0"),
+                DiffLine::Added("WHERE valor > 10"),
+                DiffLine::Added("LIMIT 5"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preview_is_recorded() {
+        let filters = DataFilters {
+            preview: Some(Preview::Sample(100)),
+            ..DataFilters::new("/tmp/Notas.csv", "AllData")
+        };
+        let entry = HistoryEntry::new(&filters).finish(Duration::ZERO, Ok(100));
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(serde_json::from_str::<HistoryEntry>(&json).unwrap(), entry);
+
+        // Entries saved before previews were recorded are full queries.
+        let old = json.replace(r#","preview":{"Sample":100}"#, "");
+        let old: HistoryEntry = serde_json::from_str(&old).unwrap();
+        assert_eq!(old.preview, None);
+    }
+
+    #[test]
+    fn test_record_and_search() {
+        let filters = DataFilters::new("/tmp/Notas.csv", "AllData");
+        let mut history = QueryHistory::default();
+        for rows in 0..MAX_ENTRIES + 2 {
+            let entry = HistoryEntry::new(&filters).finish(Duration::from_millis(5), Ok(rows));
+            history.record(entry);
+        }
+        assert_eq!(history.entries.len(), MAX_ENTRIES);
+        assert_eq!(history.entries[0].rows, Some(2)); // The oldest were dropped.
+
+        let failed =
+            HistoryEntry::new(&filters).finish(Duration::ZERO, Err("Column não encontrada"));
+        assert!(failed.matches("nao ENCONTRADA"));
+        assert!(failed.matches("notas.csv"));
+        assert!(!failed.matches("LIMIT"));
+    }
+}
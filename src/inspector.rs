@@ -0,0 +1,264 @@
+use crate::selection::cell_text;
+
+use egui::{CollapsingHeader, Label, RichText, ScrollArea, TextEdit, Ui};
+use polars::prelude::*;
+
+/// Number of bytes shown per line of the hex view.
+const HEX_WIDTH: usize = 16;
+
+/// Maximum number of bytes shown in the hex view.
+const HEX_LIMIT: usize = 64 * 1024;
+
+/// Full value of a cell, prepared for the cell inspector.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellContent {
+    /// The cell is null.
+    Null,
+    /// Plain text, shown wrapped.
+    Text(String),
+    /// Text that parses as JSON, shown pretty-printed.
+    Json(String),
+    /// Struct or list value, shown as an expandable tree.
+    Tree(TreeNode),
+    /// Binary value, shown as a hex dump.
+    Binary(Vec<u8>),
+}
+
+/// Node of the tree of a nested (struct or list) value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    /// Field name or list index.
+    pub label: String,
+    /// Value of a leaf, or a short description of a nested value.
+    pub value: String,
+    /// Fields or items of a nested value.
+    pub children: Vec<TreeNode>,
+}
+
+impl CellContent {
+    /// Classifies the value of a cell.
+    pub fn new(value: AnyValue) -> Self {
+        match value {
+            AnyValue::Null => CellContent::Null,
+            AnyValue::Binary(bytes) => CellContent::Binary(bytes.to_vec()),
+            AnyValue::BinaryOwned(bytes) => CellContent::Binary(bytes),
+            AnyValue::List(_) | AnyValue::Struct(..) | AnyValue::StructOwned(_) => {
+                CellContent::Tree(TreeNode::new("value".to_string(), value))
+            }
+            value => {
+                let text = cell_text(value);
+                match pretty_json(&text) {
+                    Some(json) => CellContent::Json(json),
+                    None => CellContent::Text(text),
+                }
+            }
+        }
+    }
+
+    /// Renders the value.
+    pub fn render(&self, ui: &mut Ui) {
+        match self {
+            CellContent::Null => {
+                ui.label(RichText::new("null").italics());
+            }
+            CellContent::Text(text) => {
+                ui.add(Label::new(text.as_str()).wrap());
+            }
+            CellContent::Json(json) => {
+                ui.add(
+                    TextEdit::multiline(&mut json.as_str()) // Read only.
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            }
+            CellContent::Tree(node) => node.render(ui),
+            CellContent::Binary(bytes) => {
+                ui.label(format!("{} bytes", bytes.len()));
+                ui.add(
+                    TextEdit::multiline(&mut hex_dump(bytes).as_str()) // Read only.
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+            }
+        }
+    }
+}
+
+impl TreeNode {
+    /// Builds the tree of a value, with one child per struct field or list item.
+    pub fn new(label: String, value: AnyValue) -> Self {
+        match value {
+            AnyValue::List(series) => TreeNode {
+                label,
+                value: format!("list [{}]", series.len()),
+                children: (0..series.len())
+                    .map(|index| {
+                        let item = series.get(index).unwrap_or(AnyValue::Null);
+                        TreeNode::new(format!("[{index}]"), item)
+                    })
+                    .collect(),
+            },
+            AnyValue::Struct(..) => TreeNode::new(label, value.into_static()),
+            AnyValue::StructOwned(payload) => {
+                let (values, fields) = *payload;
+                TreeNode {
+                    label,
+                    value: format!("struct {{{}}}", fields.len()),
+                    children: fields
+                        .iter()
+                        .zip(values)
+                        .map(|(field, value)| TreeNode::new(field.name().to_string(), value))
+                        .collect(),
+                }
+            }
+            AnyValue::Null => TreeNode {
+                label,
+                value: "null".to_string(),
+                children: Vec::new(),
+            },
+            value => TreeNode {
+                label,
+                value: cell_text(value),
+                children: Vec::new(),
+            },
+        }
+    }
+
+    /// Renders the node, nested values as collapsing headers.
+    fn render(&self, ui: &mut Ui) {
+        if self.children.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new(format!("{}:", self.label)).strong());
+                ui.add(Label::new(self.value.as_str()).wrap());
+            });
+        } else {
+            CollapsingHeader::new(format!("{}: {}", self.label, self.value))
+                .id_salt(ui.next_auto_id())
+                .default_open(true)
+                .show(ui, |ui| {
+                    for child in &self.children {
+                        child.render(ui);
+                    }
+                });
+        }
+    }
+}
+
+/// Pretty-prints text holding a JSON object or array.
+pub fn pretty_json(text: &str) -> Option<String> {
+    let trimmed = text.trim_start();
+    if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+        return None; // Numbers and quoted strings are valid JSON too, but not worth it.
+    }
+
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Formats bytes as lines of offset, hex values and printable ASCII characters.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    let mut lines: Vec<String> = bytes[..bytes.len().min(HEX_LIMIT)]
+        .chunks(HEX_WIDTH)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|byte| format!("{byte:02x}")).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| match byte.is_ascii_graphic() || byte == b' ' {
+                    true => byte as char,
+                    false => '.',
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<width$}  |{ascii}|",
+                index * HEX_WIDTH,
+                hex.join(" "),
+                width = HEX_WIDTH * 3 - 1
+            )
+        })
+        .collect();
+
+    if bytes.len() > HEX_LIMIT {
+        lines.push(format!("... {} more bytes", bytes.len() - HEX_LIMIT));
+    }
+
+    lines.join("\n")
+}
+
+/// Renders the inspector of the cell at (row, column) of `df`.
+pub fn render_cell_inspector(ui: &mut Ui, df: &DataFrame, (row, column): (usize, usize)) {
+    let Some(series) = df.get_columns().get(column) else {
+        return;
+    };
+
+    ui.label(format!(
+        "{} (row {}, {})",
+        series.name(),
+        row + 1,
+        series.dtype()
+    ));
+    ui.separator();
+
+    match series.get(row) {
+        Ok(value) => {
+            ScrollArea::vertical()
+                .auto_shrink([false, true])
+                .show(ui, |ui| CellContent::new(value).render(ui));
+        }
+        Err(error) => {
+            ui.label(format!("Error: {}", error));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_and_json() {
+        let text = CellContent::new(AnyValue::String("not { json"));
+        assert_eq!(text, CellContent::Text("not { json".to_string()));
+
+        let json = CellContent::new(AnyValue::String(r#"{"a":[1,2]}"#));
+        let pretty = "{\n  \"a\": [\n    1,\n    2\n  ]\n}";
+        assert_eq!(json, CellContent::Json(pretty.to_string()));
+
+        assert_eq!(CellContent::new(AnyValue::Null), CellContent::Null);
+    }
+
+    #[test]
+    fn test_struct_and_list_tree() -> PolarsResult<()> {
+        let df = df!["a" => [1, 2], "b" => ["x", "y"]]?;
+        let df = df
+            .lazy()
+            .select([as_struct(vec![col("a"), col("b")]).alias("s")])
+            .collect()?;
+
+        let CellContent::Tree(node) = CellContent::new(df.column("s")?.get(1)?) else {
+            panic!("a struct is shown as a tree");
+        };
+        assert_eq!(node.value, "struct {2}");
+        assert_eq!(node.children[0].label, "a");
+        assert_eq!(node.children[0].value, "2");
+        assert_eq!(node.children[1].value, "y");
+
+        let list = Series::new("l".into(), [10, 20, 30]);
+        let node = TreeNode::new("value".to_string(), AnyValue::List(list));
+        assert_eq!(node.value, "list [3]");
+        assert_eq!(node.children[2].label, "[2]");
+        assert_eq!(node.children[2].value, "30");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"Hello, world!\x00\x01\xff\x7f");
+        assert_eq!(
+            dump,
+            "00000000  48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 00 01 ff  |Hello, world!...|\n\
+             00000010  7f                                               |.|"
+        );
+    }
+}
@@ -1,49 +1,86 @@
 use crate::{
-    Error, MyStyle, Popover, Settings,
-    components::{FileMetadata, file_dialog},
-    data::{DataFilters, DataFrameContainer, DataFuture},
+    ColumnDistribution, ColumnLayout, ColumnProfile, ColumnStats, ConditionalRule, Error,
+    FilterEditor, HistoryAction, HistoryEntry, LibraryAction, MyStyle, Popover, QueryHistory,
+    QueryLibrary, Settings, Tab, TabAction, Tabs,
+    components::{TableAction, file_dialog, save_file_dialog},
+    data::{DataFilters, DataFrameContainer, DataFuture, PendingTask, QueryAction},
+    generalize, render_cell_inspector,
 };
 
 use egui::{
-    CentralPanel, Context, FontId, RichText, ScrollArea, SidePanel, TopBottomPanel,
-    ViewportCommand, menu, style::Visuals, warn_if_debug_build, widgets,
+    CentralPanel, Context, FontId, Key, Modifiers, RichText, ScrollArea, SidePanel, TopBottomPanel,
+    Ui, ViewportCommand, menu, warn_if_debug_build,
 };
-use std::sync::Arc;
-use tokio::sync::oneshot::{self, error::TryRecvError};
+use polars::prelude::DataFrame;
+use std::{collections::HashMap, sync::Arc};
+
+/// Key of the column layouts in the eframe storage.
+const COLUMN_LAYOUTS_KEY: &str = "column_layouts";
+
+/// Key of the conditional formatting rules in the eframe storage.
+const CONDITIONAL_KEY: &str = "conditional_formatting";
+
+/// Views of the loaded data available in the central panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum View {
+    /// The data itself.
+    #[default]
+    Table,
+    /// One row of statistics per column.
+    Summary,
+    /// Line, bar, scatter or area chart of the data.
+    Chart,
+    /// One row at a time, as a list of columns.
+    Record,
+}
 
 /// The main application struct for PolarsView.
 pub struct PolarsViewApp {
-    /// The `DataFrameContainer` holds the loaded data (Parquet, CSV, etc.).  Using `Arc` for shared ownership and thread-safe access.
-    pub table: Arc<Option<DataFrameContainer>>,
-    /// Component for managing data filters (SQL queries, sorting, etc.).
-    pub data_filters: DataFilters,
-    /// Metadata extracted from the loaded file (if available).
-    pub metadata: Option<FileMetadata>,
+    /// Open files, each with its own data, filters, metadata and views.
+    pub tabs: Tabs,
     /// Optional popover window for displaying errors, settings, or other notifications.
     pub popover: Option<Box<dyn Popover>>,
+    /// Column order, visibility and pinning, per file.
+    column_layouts: HashMap<String, ColumnLayout>,
+    /// Whether the column manager window is open.
+    column_manager: bool,
+    /// User preferences, saved between sessions.
+    settings: Settings,
+    /// Whether the settings window is open.
+    settings_open: bool,
+    /// Column whose conditional formatting rules are being edited.
+    conditional_editor: Option<String>,
+    /// Conditional formatting rules, shared by the tabs.
+    conditional_rules: Vec<ConditionalRule>,
+    /// Queries saved by the user.
+    library: QueryLibrary,
+    /// Queries executed by the user.
+    history: QueryHistory,
+    /// Whether queries were recorded since the history file was written.
+    history_changed: bool,
 
     /// Tokio runtime for asynchronous operations (file loading, queries).
     runtime: tokio::runtime::Runtime,
-    /// Channel for receiving the result of asynchronous data loading.
-    pipe: Option<tokio::sync::oneshot::Receiver<Result<DataFrameContainer, String>>>,
-
-    /// Vector of active asynchronous tasks.  Used to prevent the application from hanging if a task fails.
-    tasks: Vec<tokio::task::JoinHandle<()>>,
 }
 
 impl Default for PolarsViewApp {
     fn default() -> Self {
         Self {
-            table: Arc::new(None),
-            data_filters: DataFilters::default(),
+            tabs: Tabs::default(),
             runtime: tokio::runtime::Builder::new_multi_thread()
                 .enable_all()
                 .build()
                 .expect("Failed to build Tokio runtime"),
-            pipe: None,
             popover: None,
-            metadata: None,
-            tasks: Vec::new(),
+            column_layouts: HashMap::new(),
+            column_manager: false,
+            settings: Settings::default(),
+            settings_open: false,
+            conditional_editor: None,
+            conditional_rules: Vec::new(),
+            library: QueryLibrary::default(),
+            history: QueryHistory::default(),
+            history_changed: false,
         }
     }
 }
@@ -51,20 +88,363 @@ impl Default for PolarsViewApp {
 impl PolarsViewApp {
     /// Creates a new `PolarsViewApp` instance.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        cc.egui_ctx.set_visuals(Visuals::dark()); // Set dark theme.
-        cc.egui_ctx.set_style_init(); // Apply custom styles.
-        Default::default()
+        Self::restore(cc)
     }
 
     /// Creates a new `PolarsViewApp` with a pre-existing `DataFuture`.  Used for asynchronous loading when the filename is known in advance.
     pub fn new_with_future(cc: &eframe::CreationContext<'_>, future: DataFuture) -> Self {
-        let mut app: Self = Default::default();
-        cc.egui_ctx.set_visuals(Visuals::dark());
-        cc.egui_ctx.set_style_init();
+        let mut app = Self::restore(cc);
         app.run_data_future(future, &cc.egui_ctx);
         app
     }
 
+    /// Creates the application with the state saved by a previous session, if any.
+    fn restore(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app: Self = Default::default();
+        if let Some(storage) = cc.storage {
+            app.column_layouts = eframe::get_value(storage, COLUMN_LAYOUTS_KEY).unwrap_or_default();
+            app.conditional_rules = eframe::get_value(storage, CONDITIONAL_KEY).unwrap_or_default();
+        }
+        app.apply_settings(Settings::load(cc.storage), &cc.egui_ctx); // Theme, fonts and formats.
+        app.tabs.list = vec![app.new_tab()];
+        match QueryLibrary::load() {
+            Ok(library) => app.library = library,
+            Err(message) => app.popover = Some(Box::new(Error { message })),
+        }
+        match QueryHistory::load() {
+            Ok(history) => app.history = history,
+            Err(message) => app.popover = Some(Box::new(Error { message })),
+        }
+        app
+    }
+
+    /// Creates an empty tab, formatted as the user prefers.
+    fn new_tab(&self) -> Tab {
+        Tab::new(&self.settings.formats, &self.conditional_rules)
+    }
+
+    /// Loads a file in the current tab if it is empty, or else in a new tab.
+    fn open_file(&mut self, filename: String, ctx: &Context) {
+        if !self.tabs.active().is_empty() {
+            self.tabs.open(self.new_tab());
+        }
+
+        let future = DataFrameContainer::load_data(filename, self.settings.csv.clone());
+        self.run_data_future(Box::new(Box::pin(future)), ctx);
+    }
+
+    /// Shows, closes or moves a tab, as requested from the tab bar.
+    fn handle_tab_action(&mut self, action: TabAction) {
+        match action {
+            TabAction::Select(index) => self.tabs.active = index,
+            TabAction::Close(index) => {
+                self.tabs.close(index);
+                if self.tabs.list.is_empty() {
+                    self.tabs.open(self.new_tab()); // There is always a tab to drop files on.
+                }
+            }
+            TabAction::Move(from, to) => self.tabs.move_tab(from, to),
+        }
+    }
+
+    /// Returns the data with the column layout of its file applied: visible columns, in display order.
+    fn apply_layout(&mut self, data: &DataFrameContainer) -> DataFrameContainer {
+        let layout = self
+            .column_layouts
+            .get(&data.filename)
+            .cloned()
+            .unwrap_or_default();
+
+        self.tabs.active_mut().apply_layout(data, layout)
+    }
+
+    /// Applies the user preferences: style of the interface and formatting of the tables.
+    fn apply_settings(&mut self, settings: Settings, ctx: &Context) {
+        ctx.set_style_init(&settings);
+        for tab in &mut self.tabs.list {
+            tab.table_state.formats = settings.formats.clone();
+            tab.summary_state.formats = settings.formats.clone();
+        }
+        self.settings = settings;
+    }
+
+    /// Shows the settings window, if open.
+    fn check_settings(&mut self, ctx: &Context) {
+        if !self.settings_open {
+            return;
+        }
+
+        let mut open = true;
+        let mut settings = self.settings.clone();
+        let mut changed = false;
+
+        egui::Window::new("Settings")
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+                    changed = settings.render(ui);
+                });
+            });
+
+        self.settings_open = open;
+        if changed {
+            self.apply_settings(settings, ctx);
+        }
+    }
+
+    /// Shows the conditional formatting rules of a column, if being edited.
+    fn check_conditional_editor(&mut self, ctx: &Context) {
+        let Some(column) = self.conditional_editor.clone() else {
+            return;
+        };
+
+        let mut open = true;
+        let mut changed = false;
+        let conditional = &mut self.tabs.active_mut().table_state.conditional;
+
+        egui::Window::new(format!("Conditional formatting: {column}"))
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                changed = conditional.render_editor(ui, &column);
+            });
+
+        if changed {
+            // The rules apply to every tab.
+            self.conditional_rules = conditional.rules.clone();
+            for tab in &mut self.tabs.list {
+                tab.table_state.conditional.rules = self.conditional_rules.clone();
+            }
+        }
+
+        if !open {
+            self.conditional_editor = None;
+        }
+    }
+
+    /// Changes the column layout of a file.
+    fn update_layout(&mut self, filename: &str, change: impl FnOnce(&mut ColumnLayout)) {
+        change(self.column_layouts.entry(filename.to_string()).or_default());
+        self.layout_changed(filename);
+    }
+
+    /// Clears what depends on the positions of the visible columns, in the tabs of a file.
+    fn layout_changed(&mut self, filename: &str) {
+        for tab in &mut self.tabs.list {
+            if tab
+                .table
+                .as_ref()
+                .as_ref()
+                .is_some_and(|table| table.filename == filename)
+            {
+                tab.layout_changed();
+            }
+        }
+    }
+
+    /// Shows the column manager of the current data, if open.
+    fn check_column_manager(&mut self, ctx: &Context) {
+        let Some(table) = self.tabs.active().table.as_ref().clone() else {
+            return;
+        };
+        if !self.column_manager {
+            return;
+        }
+
+        let mut open = true;
+        let mut changed = false;
+        let layout = self
+            .column_layouts
+            .entry(table.filename.clone())
+            .or_default();
+
+        egui::Window::new("Columns")
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                ScrollArea::vertical().max_height(500.0).show(ui, |ui| {
+                    changed = layout.render_manager(ui, &table.df);
+                });
+            });
+
+        self.column_manager = open;
+        if changed {
+            self.layout_changed(&table.filename);
+        }
+    }
+
+    /// Inserts, imports or exports saved queries, saving the library when it changes.
+    fn handle_library_action(&mut self, action: LibraryAction) {
+        let result = match action {
+            LibraryAction::Insert(sql) => {
+                self.tabs.active_mut().data_filters.query = Some(sql);
+                Ok(())
+            }
+            LibraryAction::Import => self
+                .runtime
+                .block_on(file_dialog())
+                .and_then(|path| QueryLibrary::load_from(path.as_ref()))
+                .and_then(|imported| {
+                    self.library.import(imported);
+                    self.library.save()
+                }),
+            LibraryAction::Export => self
+                .runtime
+                .block_on(save_file_dialog("queries.json"))
+                .and_then(|path| self.library.save_to(&path)),
+            LibraryAction::Changed => self.library.save(),
+        };
+
+        if let Err(message) = result {
+            self.popover = Some(Box::new(Error { message }));
+        }
+    }
+
+    /// Completes the filters of a query of the current tab: the parameters are replaced by
+    /// the values given by the user, and the other open files can be joined.
+    fn prepare_query(&mut self, mut filters: DataFilters) -> Option<DataFilters> {
+        if let Some(query) = &filters.query {
+            match self.tabs.active().params.substitute(query) {
+                Ok(sql) => filters.query = Some(sql),
+                Err(message) => {
+                    self.popover = Some(Box::new(Error { message }));
+                    return None;
+                }
+            }
+        }
+        filters.tables = self.tabs.sql_tables(self.tabs.active);
+        Some(filters)
+    }
+
+    /// Runs a SQL query in the current tab.
+    fn run_query(&mut self, filters: DataFilters, ctx: &Context) {
+        if let Some(filters) = self.prepare_query(filters) {
+            let runtime = self.runtime.handle();
+            self.tabs.active_mut().run_query(runtime, filters, ctx);
+        }
+    }
+
+    /// Computes the plan of a SQL query of the current tab, shown by the Explain window.
+    fn explain_query(&mut self, filters: DataFilters, ctx: &Context) {
+        if let Some(filters) = self.prepare_query(filters) {
+            let future = DataFrameContainer::explain_sql(filters);
+            let plan = PendingTask::spawn(self.runtime.handle(), ctx, future);
+            self.tabs.active_mut().plan = Some(plan);
+        }
+    }
+
+    /// Shows the plan of the query of the current tab, if explained.
+    fn check_query_plan(&mut self, ctx: &Context) {
+        let tab = self.tabs.active_mut();
+        let Some(plan) = tab.plan.as_mut() else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Query Plan")
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| match plan.poll() {
+                None => {
+                    ui.spinner(); // Polars is still planning the query.
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                Some(Ok(plan)) => {
+                    ui.label(RichText::new(&plan.query).monospace().strong());
+                    ScrollArea::vertical().max_height(600.0).show(ui, |ui| {
+                        egui::CollapsingHeader::new("Optimized plan")
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.label(RichText::new(&plan.optimized).monospace());
+                            });
+                        egui::CollapsingHeader::new("Unoptimized plan").show(ui, |ui| {
+                            ui.label(RichText::new(&plan.unoptimized).monospace());
+                        });
+                    });
+                }
+            });
+
+        if !open {
+            tab.plan = None;
+        }
+    }
+
+    /// Runs again a query of the history in the tab of its file, or else in a new tab.
+    fn rerun_query(&mut self, entry: HistoryEntry, ctx: &Context) {
+        let position = self.tabs.list.iter().position(|tab| {
+            (tab.table.as_ref().as_ref()).is_some_and(|table| table.filename == entry.filename)
+        });
+
+        let mut filters = match position {
+            Some(index) => {
+                self.tabs.active = index;
+                self.tabs.active().data_filters.clone()
+            }
+            None => {
+                if !self.tabs.active().is_empty() {
+                    self.tabs.open(self.new_tab());
+                }
+                DataFilters {
+                    csv_options: self.settings.csv.clone(),
+                    ..DataFilters::new(&entry.filename, &entry.table_name)
+                }
+            }
+        };
+        filters.table_name = Some(entry.table_name);
+        filters.query = Some(entry.sql.clone());
+        filters.preview = entry.preview; // A preview runs again as a preview.
+        if entry.csv_delimiter.is_some() {
+            filters.csv_delimiter = entry.csv_delimiter;
+        }
+
+        self.tabs.active_mut().data_filters.query = Some(entry.sql);
+        self.run_query(filters, ctx);
+    }
+
+    /// Re-runs, pins or forgets the queries of the history.
+    fn handle_history_action(&mut self, action: HistoryAction, ctx: &Context) {
+        let result = match action {
+            HistoryAction::Rerun(entry) => {
+                self.rerun_query(entry, ctx);
+                Ok(())
+            }
+            HistoryAction::Pin(entry) => {
+                let index = self.library.add(generalize(&entry.sql, &entry.table_name));
+                self.library.queries[index].description = format!("Run on {}.", entry.timestamp);
+                self.library.save()
+            }
+            HistoryAction::Clear => {
+                self.history = QueryHistory::default();
+                self.history_changed = false;
+                self.history.save()
+            }
+        };
+
+        if let Err(message) = result {
+            self.popover = Some(Box::new(Error { message }));
+        }
+    }
+
+    /// Writes the visible columns of the current data to a CSV file chosen by the user.
+    fn export_csv(&mut self) {
+        let Some(table) = self.tabs.active().table.as_ref().clone() else {
+            return;
+        };
+
+        let shown = self.apply_layout(&table);
+        let result = self
+            .runtime
+            .block_on(save_file_dialog("data.csv"))
+            .and_then(|path| shown.write_csv(path));
+
+        if let Err(message) = result {
+            self.popover = Some(Box::new(Error { message }));
+        }
+    }
+
     /// Checks if a popover is active and displays it.  If the popover is closed by the user, it is removed.
     fn check_popover(&mut self, ctx: &Context) {
         if let Some(popover) = &mut self.popover {
@@ -74,81 +454,209 @@ impl PolarsViewApp {
         }
     }
 
-    /// Checks if there is a data loading operation pending (asynchronous).
+    /// Checks the data loading operations pending (asynchronous) in every tab.
     ///
-    /// Returns `true` if data is still loading, `false` otherwise.  Also handles potential errors from the loading process.
+    /// Returns `true` if the data of the current tab is still loading, `false` otherwise.  Also handles potential errors from the loading process.
     fn check_data_pending(&mut self) -> bool {
-        // Take the receiver out of the `Option`.  This allows us to check if the data has arrived.
-        let Some(mut output) = self.pipe.take() else {
-            return false; // No data loading in progress.
-        };
+        let mut loading = false;
+
+        let table_names: Vec<String> = (0..self.tabs.list.len())
+            .map(|index| {
+                self.tabs
+                    .default_table_name(index, &self.settings.table_name)
+            })
+            .collect();
+
+        for (index, tab) in self.tabs.list.iter_mut().enumerate() {
+            match tab.check_data_pending(&table_names[index]) {
+                Ok(pending) => loading |= pending && index == self.tabs.active,
+                Err(message) => self.popover = Some(Box::new(Error { message })),
+            }
 
-        match output.try_recv() {
-            Ok(data) => match data {
-                Ok(data) => {
-                    // Data loaded successfully!
-                    let filename = data.filename.clone();
-                    dbg!(&filename);
-
-                    // Create data filters
-                    let mut data_filters = DataFilters::new(&filename);
-                    if let Some(delimiter) = &data.filters.csv_delimiter {
-                        data_filters.csv_delimiter = Some(delimiter.to_string())
-                    }
-                    self.data_filters = data_filters;
+            // Record the queries that finished; the history file is written by `save`.
+            if !tab.executed.is_empty() {
+                tab.executed
+                    .drain(..)
+                    .for_each(|entry| self.history.record(entry));
+                self.history_changed = true;
+            }
+        }
 
-                    dbg!(&data.filters);
+        loading
+    }
 
-                    // Load metadata
-                    self.metadata = FileMetadata::from_filename(&filename).ok();
-                    self.table = Arc::new(Some(data));
-                    false // Data loading complete.
-                }
-                Err(msg) => {
-                    // An error occurred during data loading.
-                    self.popover = Some(Box::new(Error { message: msg }));
-                    false // Data loading complete (with an error).
-                }
-            },
-            Err(error) => match error {
-                TryRecvError::Empty => {
-                    // Data is still loading. Put the receiver back into the `Option`.
-                    self.pipe = Some(output);
-                    true // Data loading still in progress.
-                }
-                TryRecvError::Closed => {
-                    // The data loading task was terminated unexpectedly.
-                    self.popover = Some(Box::new(Error {
-                        message: "Data operation terminated without response.".to_string(),
-                    }));
-                    false // Data loading complete (due to termination).
-                }
-            },
+    /// Runs a `DataFuture` to load data asynchronously in the current tab.
+    fn run_data_future(&mut self, future: DataFuture, ctx: &Context) {
+        let runtime = self.runtime.handle();
+        self.tabs.active_mut().run_data_future(runtime, future, ctx);
+    }
+
+    /// Displays the loaded data as a table.
+    fn show_table(&mut self, ui: &mut Ui, data: DataFrameContainer, ctx: &Context) {
+        let shown = self.apply_layout(&data); // Visible columns, in display order.
+
+        let runtime = self.runtime.handle();
+        let tab = self.tabs.active_mut();
+        tab.search
+            .show(ui, &shown.df, runtime, &mut tab.table_state);
+        shown.render_row_navigation(ui, &mut tab.table_state);
+
+        if let Some(filters) = data.render_filter_chips(ui) {
+            let future = data.clone().filter(filters);
+            tab.run_data_future(runtime, Box::new(Box::pin(future)), ctx);
+        }
+
+        // Colors of the conditional formatting rules, evaluated in the background.
+        let state = &mut tab.table_state;
+        state
+            .conditional
+            .update(&shown.df, &state.formats, runtime, ctx);
+
+        // Render the table and handle the action requested by the user, if any.
+        tab.table_state.manage_columns = true;
+        match shown.render_table(ui, &mut tab.table_state) {
+            Some(TableAction::Sort(filters)) => {
+                let future = data.sort(Some(*filters)); // Sort the data.
+                tab.run_data_future(runtime, Box::new(Box::pin(future)), ctx); // Run the sorting task.
+            }
+            Some(TableAction::Profile(column)) => self.open_profile(data.df, column, ctx),
+            Some(TableAction::Distribution(column)) => self.open_distribution(data.df, column, ctx),
+            Some(TableAction::Filter(column)) => {
+                self.open_filter_editor(View::Table, data, column, ctx)
+            }
+            Some(TableAction::HideColumn(column)) => {
+                self.update_layout(&data.filename, |layout| layout.hide(&column))
+            }
+            Some(TableAction::PinColumn(column)) => {
+                self.update_layout(&data.filename, |layout| layout.toggle_pin(&column))
+            }
+            Some(TableAction::ManageColumns) => self.column_manager = true,
+            Some(TableAction::Conditional(column)) => self.conditional_editor = Some(column),
+            Some(TableAction::Error(message)) => self.popover = Some(Box::new(Error { message })),
+            None => {}
         }
     }
 
-    /// Runs a `DataFuture` to load data asynchronously. This function takes a future, spawns a Tokio task, and sets up a channel to receive the result.
-    fn run_data_future(&mut self, future: DataFuture, ctx: &Context) {
-        // Before scheduling a new future, ensure no tasks are stuck
-        self.tasks.retain(|task| !task.is_finished());
+    /// Displays the `describe()`-like summary of the loaded data, computing it when needed.
+    fn show_summary(&mut self, ui: &mut Ui, data: DataFrameContainer, ctx: &Context) {
+        if self.tabs.active().summary.is_none() {
+            // Hidden columns are left out of the summary, as of any export.
+            let shown = self.apply_layout(&data);
+            let tab = self.tabs.active_mut();
+            let task = tab.spawn_task(self.runtime.handle(), shown.summarize(), ctx);
+            tab.summary = Some(PendingTask::Running(task));
+        }
+
+        let tab = self.tabs.active_mut();
+        let summary = match tab.summary.as_mut().and_then(|summary| summary.poll()) {
+            None => {
+                ui.centered_and_justified(|ui| {
+                    ui.spinner(); // The summary is still being computed.
+                });
+                return;
+            }
+            Some(Err(message)) => {
+                ui.label(format!("Error: {}", message));
+                return;
+            }
+            Some(Ok(summary)) => summary.clone(),
+        };
 
-        // Create a oneshot channel for sending the data from the async task to the UI thread.
-        let (tx, rx) = oneshot::channel::<Result<DataFrameContainer, String>>();
-        self.pipe = Some(rx);
+        if ui.button("Export CSV").clicked() {
+            let result = self
+                .runtime
+                .block_on(save_file_dialog("summary.csv"))
+                .and_then(|path| summary.write_csv(path));
 
-        // Clone the context for use within the asynchronous task (to request repaints).
-        let ctx_clone = ctx.clone();
+            if let Err(message) = result {
+                self.popover = Some(Box::new(Error { message }));
+            }
+        }
 
-        // Spawn an async task to load the data.
-        let handle = self.runtime.spawn(async move {
-            let data = future.await;
-            if tx.send(data).is_err() {
-                eprintln!("Receiver dropped before data could be sent."); // Handle potential error if the receiver is dropped.
+        let runtime = self.runtime.handle();
+        let tab = self.tabs.active_mut();
+        if let Some(filters) = summary.render_filter_chips(ui) {
+            let task = tab.spawn_task(runtime, summary.clone().filter(filters), ctx);
+            tab.summary = Some(PendingTask::Running(task));
+        }
+
+        match summary.render_table(ui, &mut tab.summary_state) {
+            Some(TableAction::Sort(filters)) => {
+                // The summary is sorted like any other table, replacing the current one.
+                let task = tab.spawn_task(runtime, summary.sort(Some(*filters)), ctx);
+                tab.summary = Some(PendingTask::Running(task));
             }
-            ctx_clone.request_repaint(); // Request a repaint of the UI to display the loaded data.
-        });
+            Some(TableAction::Profile(column)) => self.open_profile(summary.df, column, ctx),
+            Some(TableAction::Distribution(column)) => {
+                self.open_distribution(summary.df, column, ctx)
+            }
+            Some(TableAction::Filter(column)) => {
+                self.open_filter_editor(View::Summary, summary, column, ctx)
+            }
+            Some(TableAction::Error(message)) => self.popover = Some(Box::new(Error { message })),
+            // The column layout and colors belong to the data, not to its summary.
+            Some(
+                TableAction::HideColumn(_)
+                | TableAction::PinColumn(_)
+                | TableAction::ManageColumns
+                | TableAction::Conditional(_),
+            )
+            | None => {}
+        }
+    }
+
+    /// Opens the profile popover of a column, computing its statistics in the background.
+    fn open_profile(&mut self, df: Arc<DataFrame>, column: String, ctx: &Context) {
+        let future = ColumnStats::compute(df, column.clone());
+        let task = self
+            .tabs
+            .active_mut()
+            .spawn_task(self.runtime.handle(), future, ctx);
+        let stats = PendingTask::Running(task);
+        self.popover = Some(Box::new(ColumnProfile { column, stats }));
+    }
 
-        self.tasks.push(handle); // Track the task.
+    /// Opens the distribution chart of a column, computing it in the background.
+    fn open_distribution(&mut self, df: Arc<DataFrame>, column: String, ctx: &Context) {
+        let distribution = ColumnDistribution::new(self.runtime.handle(), ctx, df, column);
+        self.popover = Some(Box::new(distribution));
+    }
+
+    /// Opens the filter editor of a column of the data shown in `view`.
+    fn open_filter_editor(
+        &mut self,
+        view: View,
+        data: DataFrameContainer,
+        column: String,
+        ctx: &Context,
+    ) {
+        let editor = FilterEditor::new(self.runtime.handle(), ctx, data, column);
+        self.tabs.active_mut().filter_editor = Some((view, editor));
+    }
+
+    /// Shows the filter editor of the current tab, if open, and filters its data once the user applies the filter.
+    fn check_filter_editor(&mut self, ctx: &Context) {
+        let runtime = self.runtime.handle();
+        let tab = self.tabs.active_mut();
+        let Some((view, editor)) = &mut tab.filter_editor else {
+            return;
+        };
+
+        let view = *view;
+        let applied = editor.show(ctx);
+        if !editor.open {
+            tab.filter_editor = None;
+        }
+
+        if let Some((data, filters)) = applied {
+            match view {
+                View::Summary => {
+                    let task = tab.spawn_task(runtime, data.filter(filters), ctx);
+                    tab.summary = Some(PendingTask::Running(task));
+                }
+                _ => tab.run_data_future(runtime, Box::new(Box::pin(data.filter(filters))), ctx),
+            }
+        }
     }
 }
 
@@ -157,22 +665,50 @@ impl PolarsViewApp {
 // https://rodneylab.com/trying-egui/
 
 impl eframe::App for PolarsViewApp {
+    /// Saves the settings, column layouts and conditional formatting rules, restored on the next start.
+    ///
+    /// Also writes the query history, if queries were recorded: periodically and on exit,
+    /// rather than after every query.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, COLUMN_LAYOUTS_KEY, &self.column_layouts);
+        self.settings.save(storage);
+        eframe::set_value(storage, CONDITIONAL_KEY, &self.conditional_rules);
+
+        if std::mem::take(&mut self.history_changed) {
+            if let Err(message) = self.history.save() {
+                self.popover = Some(Box::new(Error { message }));
+            }
+        }
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         // Check and display any active popovers (errors, settings, etc.).
         self.check_popover(ctx);
+        self.check_filter_editor(ctx);
+        self.check_column_manager(ctx);
+        self.check_settings(ctx);
+        self.check_conditional_editor(ctx);
+        self.check_query_plan(ctx);
+
+        // Ctrl+F searches the cells of the table.
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::F)) {
+            let tab = self.tabs.active_mut();
+            tab.view = View::Table;
+            tab.search.open();
+        }
 
-        // Handle dropped files.
-        if let Some(dropped_file) = ctx.input(|i| i.raw.dropped_files.last().cloned()) {
-            if let Some(path) = &dropped_file.path {
-                if let Some(filename) = path.to_str() {
-                    // Load data from the dropped file.
-                    self.run_data_future(
-                        Box::new(Box::pin(DataFrameContainer::load_data(
-                            filename.to_string(),
-                        ))),
-                        ctx,
-                    );
-                }
+        // Ctrl+G goes to a row of the table.
+        if ctx.input_mut(|i| i.consume_key(Modifiers::COMMAND, Key::G)) {
+            let tab = self.tabs.active_mut();
+            tab.view = View::Table;
+            tab.table_state.go_to.focus = true;
+        }
+
+        // Handle dropped files, each one opened in its own tab.
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for dropped_file in dropped_files {
+            if let Some(filename) = dropped_file.path.as_ref().and_then(|path| path.to_str()) {
+                self.open_file(filename.to_string(), ctx);
             }
         }
 
@@ -181,6 +717,7 @@ impl eframe::App for PolarsViewApp {
         // Using static layout until I put together a TabTree that can make this dynamic
         //
         //  | menu_bar        widgets |
+        //  | tab bar                 |
         //  ---------------------------
         //  |         |               |
         //  | Data    |     main      |
@@ -189,6 +726,7 @@ impl eframe::App for PolarsViewApp {
         //  ---------------------------
         //  | notification footer     |
 
+        let mut switch_theme = false;
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             menu::bar(ui, |ui| {
                 ui.horizontal(|ui| {
@@ -196,17 +734,20 @@ impl eframe::App for PolarsViewApp {
                         if ui.button("Open").clicked() {
                             // Open a file dialog to select a file.
                             if let Ok(filename) = self.runtime.block_on(file_dialog()) {
-                                self.run_data_future(
-                                    Box::new(Box::pin(DataFrameContainer::load_data(filename))),
-                                    ctx,
-                                );
+                                self.open_file(filename, ctx);
                             }
                             ui.close_menu();
                         }
 
+                        if ui.button("Export CSV").clicked() {
+                            // Export the visible columns of the current data.
+                            self.export_csv();
+                            ui.close_menu();
+                        }
+
                         if ui.button("Settings").clicked() {
-                            // Show the settings popover.
-                            self.popover = Some(Box::new(Settings {}));
+                            // Show the settings window.
+                            self.settings_open = true;
                             ui.close_menu();
                         }
 
@@ -230,48 +771,117 @@ impl eframe::App for PolarsViewApp {
                     let delta = ui.available_width() - 15.0;
                     if delta > 0.0 {
                         ui.add_space(delta);
-                        widgets::global_theme_preference_switch(ui);
+                        // Switches the theme of the settings, saved with them.
+                        let (icon, hover) = match self.settings.dark_mode {
+                            true => ("☀", "Switch to light mode"),
+                            false => ("🌙", "Switch to dark mode"),
+                        };
+                        if ui.button(icon).on_hover_text(hover).clicked() {
+                            switch_theme = true;
+                        }
                     }
                 });
             });
         });
 
+        if switch_theme {
+            let settings = Settings {
+                dark_mode: !self.settings.dark_mode,
+                ..self.settings.clone()
+            };
+            self.apply_settings(settings, ctx);
+        }
+
+        TopBottomPanel::top("tab_bar").show(ctx, |ui| {
+            if let Some(action) = self.tabs.render_bar(ui) {
+                self.handle_tab_action(action);
+            }
+        });
+
         SidePanel::left("side_panel")
             .resizable(true)
             .show(ctx, |ui| {
                 ScrollArea::vertical().show(ui, |ui| {
                     // Add Metadata section
-                    if let Some(metadata) = &self.metadata {
+                    if let Some(metadata) = &self.tabs.active().metadata {
                         ui.collapsing("Metadata", |ui| {
                             metadata.render_metadata(ui);
                         });
                     }
 
                     // Add Query section
+                    let mut applied = None;
                     ui.collapsing("Query", |ui| {
-                        if let Some(filters) = self.data_filters.render_filter(ui) {
-                            // Load data with the applied query.
-                            self.run_data_future(
-                                Box::new(Box::pin(DataFrameContainer::load_data_with_sql(filters))),
-                                ctx,
-                            );
-                        }
+                        let completions = self.tabs.completions();
+                        let tab = self.tabs.active_mut();
+                        let error = &mut tab.query_error;
+                        applied = tab.data_filters.render_filter(ui, error, &completions);
+
+                        // Inputs of the parameters of the query.
+                        let query = tab.data_filters.query.as_deref().unwrap_or_default();
+                        tab.params.sync(query);
+                        tab.params.render(ui);
+                    });
+                    match applied {
+                        Some(QueryAction::Apply(filters)) => self.run_query(filters, ctx),
+                        Some(QueryAction::Explain(filters)) => self.explain_query(filters, ctx),
+                        None => {}
+                    }
+
+                    // Add the saved queries, for the table of the current tab.
+                    let mut library_action = None;
+                    ui.collapsing("Saved Queries", |ui| {
+                        let filters = &self.tabs.active().data_filters;
+                        let current = filters.query.as_deref().unwrap_or_default();
+                        let table_name = filters.table_name.as_deref().unwrap_or_default();
+                        library_action = self.library.render(ui, current, table_name);
+                    });
+                    if let Some(action) = library_action {
+                        self.handle_library_action(action);
+                    }
+
+                    // Add the queries executed, to run, pin or compare them.
+                    let mut history_action = None;
+                    ui.collapsing("History", |ui| {
+                        let filters = &self.tabs.active().data_filters;
+                        let current = filters.query.as_deref().unwrap_or_default();
+                        history_action = self.history.render(ui, current);
+                    });
+                    if let Some(action) = history_action {
+                        self.handle_history_action(action, ctx);
+                    }
+
+                    // Add the tables of the queries: every open file.
+                    ui.collapsing("Tables", |ui| {
+                        self.tabs.render_tables(ui);
                     });
 
                     // Add Schema section
-                    if let Some(metadata) = &self.metadata {
+                    let tab = self.tabs.active();
+                    if let Some(metadata) = &tab.metadata {
                         ui.collapsing("Schema", |ui| {
                             metadata.render_schema(ui);
                         });
                     }
+
+                    // Add the schema of the current query result, whatever the file format.
+                    if let Some(table) = tab.table.as_ref() {
+                        ui.collapsing("Result Schema", |ui| {
+                            table.render_schema(ui);
+                        });
+                    }
                 });
             });
 
         TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             // Display the filename of the loaded data.
-            ui.horizontal(|ui| match &*self.table {
+            ui.horizontal(|ui| match self.tabs.active().table.as_ref() {
                 Some(table) => {
                     ui.label(format!("{:#?}", table.filename));
+                    if let Some(preview) = table.filters.preview {
+                        ui.separator();
+                        ui.colored_label(ui.visuals().warn_fg_color, preview.label());
+                    }
                 }
                 None => {
                     ui.label("no file set");
@@ -279,6 +889,30 @@ impl eframe::App for PolarsViewApp {
             });
         });
 
+        // Full value of the selected cell.
+        let tab = self.tabs.active();
+        let selected_cell = tab.table_state.selection.map(|selection| selection.cursor);
+        if let (true, View::Table, Some(cell), Some(table)) = (
+            tab.table_state.inspector,
+            tab.view,
+            selected_cell,
+            tab.table.as_ref().clone(),
+        ) {
+            let shown = self.apply_layout(&table); // The selection refers to the visible columns.
+            TopBottomPanel::bottom("cell_inspector")
+                .resizable(true)
+                .default_height(200.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading("Cell Inspector");
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            self.tabs.active_mut().table_state.inspector = false;
+                        }
+                    });
+                    render_cell_inspector(ui, &shown.df, cell);
+                });
+        }
+
         // Main table display area.
         // https://whoisryosuke.com/blog/2023/getting-started-with-egui-in-rust
         // https://github.com/emilk/egui/issues/1376
@@ -289,16 +923,38 @@ impl eframe::App for PolarsViewApp {
         CentralPanel::default().show(ctx, |ui| {
             warn_if_debug_build(ui); // Show a warning in debug builds.
 
-            match self.table.as_ref().clone() {
+            match self.tabs.active().table.as_ref().clone() {
                 Some(parquet_data) if parquet_data.df.width() > 0 => {
-                    // Data loaded successfully, display the table.
-                    ScrollArea::horizontal().show(ui, |ui| {
-                        let opt_filters = parquet_data.render_table(ui); // Render the table and get any filter updates.
-                        if let Some(filters) = opt_filters {
-                            let future = parquet_data.sort(Some(filters)); // Sort the data.
-                            self.run_data_future(Box::new(Box::pin(future)), ctx); // Run the sorting task.
+                    // Select between the data itself and its summary.
+                    let tab = self.tabs.active_mut();
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut tab.view, View::Table, "Table");
+                        ui.selectable_value(&mut tab.view, View::Summary, "Summary");
+                        ui.selectable_value(&mut tab.view, View::Chart, "Chart");
+                        let record = ui.selectable_value(&mut tab.view, View::Record, "Record");
+                        if let (true, Some(selection)) =
+                            (record.clicked(), tab.table_state.selection)
+                        {
+                            tab.record.row = selection.cursor.0; // Start at the selected row.
                         }
                     });
+                    ui.separator();
+
+                    match tab.view {
+                        View::Table => self.show_table(ui, parquet_data, ctx),
+                        View::Summary => self.show_summary(ui, parquet_data, ctx),
+                        View::Record => {
+                            let shown = self.apply_layout(&parquet_data);
+                            self.tabs.active_mut().record.show(ui, &shown.df);
+                        }
+                        View::Chart => {
+                            let handle = self.runtime.handle().clone();
+                            let chart = &mut self.tabs.active_mut().chart;
+                            if let Some(popover) = chart.show(ui, &parquet_data, &handle) {
+                                self.popover = Some(popover);
+                            }
+                        }
+                    }
                 }
                 _ => {
                     // No data loaded yet, show a prompt.
@@ -311,7 +967,7 @@ impl eframe::App for PolarsViewApp {
             // Show a loading spinner if data is currently being loaded.
             if self.check_data_pending() {
                 ui.disable(); // Disable UI interaction while loading.
-                if self.table.as_ref().is_none() {
+                if self.tabs.active().table.is_none() {
                     ui.centered_and_justified(|ui| {
                         // Show spinner while loading initial data.
                         ui.spinner();
@@ -1,13 +1,34 @@
 // Modules that make up the ParqBench library.
 mod args;
+mod charts;
+mod columns;
+mod completion;
 mod components;
+mod conditional;
 mod data;
+mod editor;
+mod filters;
+mod formats;
+mod history;
+mod inspector;
 mod layout;
+mod params;
+mod plots;
+mod record;
+mod search;
+mod selection;
+mod settings;
 mod sqls;
+mod stats;
+mod tabs;
 mod traits;
 
 // Publicly expose the contents of these modules.
-pub use self::{args::Arguments, components::*, data::*, layout::*, sqls::*, traits::*};
+pub use self::{
+    args::Arguments, charts::*, columns::*, completion::*, components::*, conditional::*, data::*,
+    editor::*, filters::*, formats::*, history::*, inspector::*, layout::*, params::*, plots::*,
+    record::*, search::*, settings::*, sqls::*, stats::*, tabs::*, traits::*,
+};
 
 use polars::{
     error::PolarsResult,
@@ -33,6 +54,23 @@ pub fn get_extension(filename: &str) -> Option<String> {
         .map(|ext| ext.to_lowercase()) // Convert the extension to lowercase for case-insensitive comparison
 }
 
+/// Formats a size in bytes using binary units (KiB, MiB, ...).
+pub fn format_file_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["bytes", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} {}", UNITS[0]),
+        _ => format!("{size:.2} {}", UNITS[unit]),
+    }
+}
+
 /// Filtra colunas do tipo float64.
 ///
 /// Posteriormente, arredonda os valores da coluna
@@ -74,6 +112,14 @@ mod tests {
         assert_eq!(get_extension(""), None); // Empty filename
     }
 
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(0), "0 bytes");
+        assert_eq!(format_file_size(1023), "1023 bytes");
+        assert_eq!(format_file_size(1536), "1.50 KiB");
+        assert_eq!(format_file_size(5 * 1024 * 1024 * 1024), "5.00 GiB");
+    }
+
     #[test]
     fn test_path_with_dots() {
         assert_eq!(get_extension("path.to.file.txt"), Some("txt".to_string()));
@@ -1,7 +1,7 @@
 #![warn(clippy::all)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use polars_view::{Arguments, DataFilters, DataFrameContainer, PolarsViewApp};
+use polars_view::{Arguments, DataFilters, DataFrameContainer, PolarsViewApp, Settings};
 
 /*
 cargo fmt
@@ -39,8 +39,9 @@ fn main() -> eframe::Result<()> {
                     // Log debug information about the data filters.
                     DataFilters::debug(&args);
 
-                    // Load the Parquet data from the specified filename.
-                    let future = DataFrameContainer::load_data(filename.to_string());
+                    // Load the data from the specified filename, with the saved CSV options.
+                    let csv_options = Settings::load(cc.storage).csv;
+                    let future = DataFrameContainer::load_data(filename.to_string(), csv_options);
 
                     // Create a new PolarsViewApp with the data loading future.
                     PolarsViewApp::new_with_future(cc, Box::new(Box::pin(future)))
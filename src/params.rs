@@ -0,0 +1,356 @@
+use crate::{ErrorLocation, TokenKind, fold, tokenize};
+
+use chrono::NaiveDate;
+use egui::{ComboBox, Grid, TextEdit, Ui};
+use std::ops::Range;
+
+/// Type of the value of a query parameter, which decides its input and its quoting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParamKind {
+    #[default]
+    Text,
+    Number,
+    /// A date as YYYY-MM-DD.
+    Date,
+    Boolean,
+}
+
+impl ParamKind {
+    /// Name shown by the type selector.
+    fn label(&self) -> &'static str {
+        match self {
+            ParamKind::Text => "Text",
+            ParamKind::Number => "Number",
+            ParamKind::Date => "Date",
+            ParamKind::Boolean => "Boolean",
+        }
+    }
+
+    /// Type expected from the name of the parameter: `:year` and `:mes` are numbers,
+    /// `{{data_inicial}}` is a date.
+    fn guess(name: &str) -> Self {
+        let name = fold(name);
+        let has = |words: &[&str]| words.iter().any(|word| name.contains(word));
+
+        if has(&["date", "data", "dia_", "_dia"]) {
+            ParamKind::Date
+        } else if has(&["year", "month", "ano", "mes", "limit", "count", "num"]) {
+            ParamKind::Number
+        } else {
+            ParamKind::Text
+        }
+    }
+
+    /// SQL literal of a value, quoted so that it cannot change the query.
+    fn literal(&self, value: &str) -> Result<String, String> {
+        let value = value.trim();
+        match self {
+            ParamKind::Text => Ok(format!("'{}'", value.replace('\'', "''"))),
+            // Negative numbers in brackets, so that `a-:delta` does not become a `--` comment.
+            ParamKind::Number => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() && value.starts_with('-') => {
+                    Ok(format!("({value})"))
+                }
+                Ok(number) if number.is_finite() => Ok(value.to_string()),
+                _ => Err(format!("{value:?} is not a number")),
+            },
+            ParamKind::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| format!("DATE('{}')", date.format("%Y-%m-%d")))
+                .map_err(|_| format!("{value:?} is not a date (YYYY-MM-DD)")),
+            ParamKind::Boolean => match value {
+                "true" => Ok("TRUE".to_string()),
+                _ => Ok("FALSE".to_string()),
+            },
+        }
+    }
+}
+
+/// A parameter of the query, with the value given by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParam {
+    pub name: String,
+    pub kind: ParamKind,
+    pub value: String,
+}
+
+/// Parameters of a query, written `:name` or `{{name}}`, and their byte ranges.
+///
+/// Strings, comments and casts such as `valor::int` are not parameters.
+pub fn find_parameters(sql: &str) -> Vec<(String, Range<usize>)> {
+    // Tokens without the spaces, allowed inside the braces.
+    let tokens: Vec<_> = tokenize(sql)
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Whitespace)
+        .collect();
+    let text = |index: usize| tokens.get(index).map(|token| &sql[token.range.clone()]);
+    let is_name = |index: usize| {
+        tokens.get(index).is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Identifier | TokenKind::Keyword | TokenKind::Function
+            )
+        })
+    };
+
+    let mut parameters = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let start = tokens[index].range.start;
+
+        // :name, right after the colon, which is not part of a `::` cast.
+        let colon = text(index) == Some(":")
+            && (index == 0 || text(index - 1) != Some(":") || tokens[index - 1].range.end < start)
+            && text(index + 1) != Some(":")
+            && is_name(index + 1)
+            && tokens[index + 1].range.start == tokens[index].range.end;
+        if colon {
+            let name = tokens[index + 1].range.clone();
+            parameters.push((sql[name.clone()].to_string(), start..name.end));
+            index += 2;
+            continue;
+        }
+
+        // {{name}}
+        let braces = [index, index + 1, index + 3, index + 4].map(text)
+            == [Some("{"), Some("{"), Some("}"), Some("}")];
+        if braces && is_name(index + 2) {
+            let name = &sql[tokens[index + 2].range.clone()];
+            parameters.push((name.to_string(), start..tokens[index + 4].range.end));
+            index += 5;
+            continue;
+        }
+
+        index += 1;
+    }
+    parameters
+}
+
+/// Values of the parameters of the query of a tab.
+#[derive(Debug, Clone, Default)]
+pub struct QueryParams {
+    pub params: Vec<QueryParam>,
+}
+
+impl QueryParams {
+    /// Follows the parameters of the query, keeping the values of those still there.
+    pub fn sync(&mut self, sql: &str) {
+        let mut names: Vec<String> = Vec::new();
+        for (name, _) in find_parameters(sql) {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        if names.iter().eq(self.params.iter().map(|param| &param.name)) {
+            return;
+        }
+
+        let mut previous = std::mem::take(&mut self.params);
+        self.params = names
+            .into_iter()
+            .map(
+                |name| match previous.iter().position(|param| param.name == name) {
+                    Some(index) => previous.swap_remove(index),
+                    None => QueryParam {
+                        kind: ParamKind::guess(&name),
+                        name,
+                        value: String::new(),
+                    },
+                },
+            )
+            .collect();
+    }
+
+    /// Replaces the parameters of the query by the literals of their values.
+    pub fn substitute(&self, sql: &str) -> Result<String, String> {
+        let mut result = String::with_capacity(sql.len());
+        let mut end = 0;
+
+        for (range, literal) in self.literals(sql)? {
+            result.push_str(&sql[end..range.start]);
+            result.push_str(&literal);
+            end = range.end;
+        }
+
+        result.push_str(&sql[end..]);
+        Ok(result)
+    }
+
+    /// Location in the query of an error found in the query with its parameters substituted.
+    ///
+    /// An error inside the value of a parameter is located at the parameter.
+    pub fn error_location(&self, sql: &str, location: ErrorLocation) -> Option<ErrorLocation> {
+        let substituted = self.substitute(sql).ok()?;
+        let offset = location.offset(&substituted);
+
+        // Substituted text minus query text, before the current parameter.
+        let mut shift = 0isize;
+        for (range, literal) in self.literals(sql).ok()? {
+            let start = range.start.checked_add_signed(shift)?;
+            if offset < start {
+                break;
+            }
+            if offset < start + literal.len() {
+                return Some(ErrorLocation::at_offset(sql, range.start));
+            }
+            shift += literal.len() as isize - range.len() as isize;
+        }
+
+        let offset = offset.checked_add_signed(-shift)?;
+        Some(ErrorLocation::at_offset(sql, offset))
+    }
+
+    /// Byte ranges of the parameters of the query, with the literals of their values.
+    fn literals(&self, sql: &str) -> Result<Vec<(Range<usize>, String)>, String> {
+        let mut literals = Vec::new();
+
+        for (name, range) in find_parameters(sql) {
+            let param = self
+                .params
+                .iter()
+                .find(|param| param.name == name)
+                .ok_or_else(|| format!("Parameter {name}: no value"))?;
+            if param.value.trim().is_empty() && param.kind != ParamKind::Text {
+                return Err(format!("Parameter {name}: no value"));
+            }
+            let literal = param
+                .kind
+                .literal(&param.value)
+                .map_err(|e| format!("Parameter {name}: {e}"))?;
+            literals.push((range, literal));
+        }
+
+        Ok(literals)
+    }
+
+    /// Renders an input, of the type chosen, for each parameter.
+    pub fn render(&mut self, ui: &mut Ui) {
+        if self.params.is_empty() {
+            return;
+        }
+
+        ui.label("Parameters:");
+        Grid::new("query_params").num_columns(3).show(ui, |ui| {
+            for param in &mut self.params {
+                ui.label(&param.name);
+
+                ComboBox::from_id_salt(("param_kind", &param.name))
+                    .selected_text(param.kind.label())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for kind in [
+                            ParamKind::Text,
+                            ParamKind::Number,
+                            ParamKind::Date,
+                            ParamKind::Boolean,
+                        ] {
+                            ui.selectable_value(&mut param.kind, kind, kind.label());
+                        }
+                    });
+
+                match param.kind {
+                    ParamKind::Boolean => {
+                        let mut checked = param.value == "true";
+                        ui.checkbox(&mut checked, "");
+                        param.value = checked.to_string();
+                    }
+                    kind => {
+                        let hint = match kind {
+                            ParamKind::Date => "YYYY-MM-DD",
+                            ParamKind::Number => "0",
+                            _ => "",
+                        };
+                        let response = ui.add(
+                            TextEdit::singleline(&mut param.value)
+                                .hint_text(hint)
+                                .desired_width(120.0),
+                        );
+                        let empty = param.value.trim().is_empty();
+                        if let (false, Err(error)) = (empty, kind.literal(&param.value)) {
+                            response.on_hover_text(&error);
+                            ui.colored_label(ui.visuals().error_fg_color, "⚠");
+                        }
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_parameters() {
+        let sql = "SELECT valor::int FROM t -- :comment\n\
+                   WHERE `Ano do Período de Apuração` = :year AND mes = {{ month }} AND x = ':text'";
+        let names: Vec<String> = find_parameters(sql)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["year", "month"]);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let sql = "SELECT * FROM t WHERE ano = :year AND nome = {{name}} AND dia >= :data_inicial";
+        let mut params = QueryParams::default();
+        params.sync(sql);
+        let kinds: Vec<ParamKind> = params.params.iter().map(|param| param.kind).collect();
+        assert_eq!(kinds, [ParamKind::Number, ParamKind::Text, ParamKind::Date]);
+
+        params.params[0].value = "2020".to_string();
+        params.params[1].value = "O'Brien'; DROP TABLE t; --".to_string();
+        params.params[2].value = "2020-01-31".to_string();
+        assert_eq!(
+            params.substitute(sql).unwrap(),
+            "SELECT * FROM t WHERE ano = 2020 AND nome = 'O''Brien''; DROP TABLE t; --' \
+             AND dia >= DATE('2020-01-31')"
+        );
+
+        params.params[0].value = "-1".to_string();
+        assert!(
+            params
+                .substitute(sql)
+                .unwrap()
+                .starts_with("SELECT * FROM t WHERE ano = (-1) AND")
+        );
+
+        params.params[0].value = "2020 OR 1=1".to_string();
+        assert_eq!(
+            params.substitute(sql),
+            Err("Parameter year: \"2020 OR 1=1\" is not a number".to_string())
+        );
+    }
+
+    #[test]
+    fn test_error_location() {
+        let sql = "SELECT * FROM t\nWHERE ano = :year AND FORM x";
+        let mut params = QueryParams::default();
+        params.sync(sql);
+        params.params[0].value = "2020".to_string();
+
+        // "FORM" is at column 22 once :year is replaced by 2020, at column 23 in the editor.
+        let location = ErrorLocation {
+            line: 2,
+            column: 22,
+        };
+        let expected = ErrorLocation {
+            line: 2,
+            column: 23,
+        };
+        assert_eq!(params.error_location(sql, location), Some(expected));
+
+        // Inside the value: at the parameter.
+        let location = ErrorLocation {
+            line: 2,
+            column: 14,
+        };
+        let expected = ErrorLocation {
+            line: 2,
+            column: 13,
+        };
+        assert_eq!(params.error_location(sql, location), Some(expected));
+    }
+}
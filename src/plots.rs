@@ -0,0 +1,461 @@
+use crate::{Popover, data::PendingTask, stats::top_values};
+
+use egui::{
+    Align2, Color32, Context, FontId, Pos2, Rect, Response, Sense, Slider, Stroke, Ui, Vec2,
+};
+use polars::prelude::*;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Number of histogram bins (or string categories) shown by default.
+pub const DEFAULT_BINS: usize = 20;
+
+/// Value distribution of a numeric or temporal column, grouped into bins of equal width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Data type of the column, used to format the bin edges.
+    pub dtype: DataType,
+    /// Lower edge of the first bin.
+    pub start: f64,
+    /// Width of every bin.
+    pub width: f64,
+    /// Number of non-null values in each bin.
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Computes a histogram of the column `name` with `bins` bins of equal width.
+    ///
+    /// Temporal columns are binned on their physical representation (days, or time units
+    /// since the epoch), so the edges can be formatted back as dates.
+    pub fn from_dataframe(df: &DataFrame, name: &str, bins: usize) -> PolarsResult<Self> {
+        let dtype = df.column(name)?.dtype().clone();
+        let bins = bins.max(1);
+
+        let values = match dtype.is_temporal() {
+            true => col(name).to_physical(),
+            false => col(name),
+        };
+
+        let lazyframe = df
+            .select([name])?
+            .lazy()
+            .select([values.cast(DataType::Float64).alias("value")])
+            .filter(col("value").is_not_null().and(col("value").is_not_nan()));
+
+        let extremes = lazyframe
+            .clone()
+            .select([
+                col("value").min().alias("min"),
+                col("value").max().alias("max"),
+            ])
+            .collect()?;
+
+        let min: Option<f64> = extremes.column("min")?.get(0)?.extract();
+        let max: Option<f64> = extremes.column("max")?.get(0)?.extract();
+
+        let (Some(min), Some(max)) = (min, max) else {
+            // Only nulls: nothing to count.
+            return Ok(Self {
+                dtype,
+                start: 0.0,
+                width: 1.0,
+                counts: vec![0; bins],
+            });
+        };
+
+        let width = match max > min {
+            true => (max - min) / bins as f64,
+            false => 1.0, // A single distinct value falls into the first bin.
+        };
+
+        // The maximum value belongs to the last bin, which is closed on the right.
+        let last = (bins - 1) as i64;
+        let bin = ((col("value") - lit(min)) / lit(width))
+            .floor()
+            .cast(DataType::Int64);
+
+        let grouped = lazyframe
+            .select([when(bin.clone().gt(lit(last)))
+                .then(lit(last))
+                .otherwise(bin)
+                .alias("bin")])
+            .group_by([col("bin")])
+            .agg([len().alias("count")])
+            .collect()?;
+
+        let mut counts = vec![0; bins];
+        let bin_indexes = grouped.column("bin")?.i64()?;
+        let bin_counts = grouped.column("count")?.idx()?;
+        for (bin, count) in bin_indexes.iter().zip(bin_counts.iter()) {
+            if let (Some(bin), Some(count)) = (bin, count) {
+                counts[bin as usize] += count as usize;
+            }
+        }
+
+        Ok(Self {
+            dtype,
+            start: min,
+            width,
+            counts,
+        })
+    }
+
+    /// Returns the lower edge of bin `index` (or the upper edge of the last bin for `index == len`).
+    pub fn edge(&self, index: usize) -> f64 {
+        self.start + index as f64 * self.width
+    }
+
+    /// Formats the range covered by bin `index`.
+    pub fn bin_label(&self, index: usize) -> String {
+        let close = if index + 1 == self.counts.len() {
+            ']'
+        } else {
+            ')'
+        };
+        format!(
+            "[{}, {}{close}",
+            format_axis_value(self.edge(index), &self.dtype),
+            format_axis_value(self.edge(index + 1), &self.dtype)
+        )
+    }
+}
+
+/// Distribution of the values of one column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Distribution {
+    /// Histogram of a numeric or temporal column.
+    Histogram(Histogram),
+    /// Most frequent values of any other column, most frequent first.
+    Categories(Vec<(String, usize)>),
+}
+
+impl Distribution {
+    /// Computes the distribution of a column in the background.
+    pub async fn compute(df: Arc<DataFrame>, name: String, bins: usize) -> Result<Self, String> {
+        Self::from_dataframe(&df, &name, bins)
+            .map_err(|e| format!("Polars distribution error: {}", e))
+    }
+
+    /// Computes a histogram for numbers and dates, or the top `bins` categories otherwise.
+    pub fn from_dataframe(df: &DataFrame, name: &str, bins: usize) -> PolarsResult<Self> {
+        let dtype = df.column(name)?.dtype();
+
+        if dtype.is_primitive_numeric() || dtype.is_decimal() || dtype.is_temporal() {
+            Ok(Self::Histogram(Histogram::from_dataframe(df, name, bins)?))
+        } else {
+            let categories = top_values(df.select([name])?.lazy(), name, bins.max(1))?;
+            Ok(Self::Categories(categories))
+        }
+    }
+
+    /// Paints the distribution: vertical bars for histograms, horizontal bars for categories.
+    pub fn paint(&self, ui: &mut Ui) {
+        match self {
+            Distribution::Histogram(histogram) => paint_histogram(ui, histogram),
+            Distribution::Categories(categories) => paint_categories(ui, categories),
+        }
+    }
+}
+
+/// Formats a value of a chart axis according to the data type it came from.
+pub fn format_axis_value(value: f64, dtype: &DataType) -> String {
+    match dtype {
+        DataType::Date => AnyValue::Date(value.round() as i32).to_string(),
+        DataType::Datetime(unit, _) => {
+            AnyValue::Datetime(value.round() as i64, *unit, None).to_string()
+        }
+        _ if value.fract() == 0.0 => format!("{value:.0}"),
+        _ => format!("{value:.2}"),
+    }
+}
+
+/// Font used by the chart labels.
+fn chart_font() -> FontId {
+    FontId::proportional(12.0)
+}
+
+/// Paints a histogram as vertical bars, with the bin range and count in a tooltip.
+fn paint_histogram(ui: &mut Ui, histogram: &Histogram) {
+    let size = Vec2::new(
+        ui.available_width(),
+        300.0_f32.max(ui.available_height() - 10.0),
+    );
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+
+    let text_color = ui.visuals().text_color();
+    let max_count = histogram.counts.iter().copied().max().unwrap_or(0).max(1);
+
+    // Leave room for the count labels on the left and the edge labels at the bottom.
+    let plot = Rect::from_min_max(
+        response.rect.min + Vec2::new(50.0, 10.0),
+        response.rect.max - Vec2::new(10.0, 25.0),
+    );
+    painter.rect_stroke(
+        plot,
+        0.0,
+        Stroke::new(1.0, Color32::GRAY),
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        plot.left_top() - Vec2::new(5.0, 0.0),
+        Align2::RIGHT_TOP,
+        max_count.to_string(),
+        chart_font(),
+        text_color,
+    );
+    painter.text(
+        plot.left_bottom() - Vec2::new(5.0, 0.0),
+        Align2::RIGHT_BOTTOM,
+        "0",
+        chart_font(),
+        text_color,
+    );
+
+    let bins = histogram.counts.len().max(1);
+    let bar_width = plot.width() / bins as f32;
+    let hovered = response
+        .hover_pos()
+        .filter(|pos| plot.contains(*pos))
+        .map(|pos| (((pos.x - plot.left()) / bar_width) as usize).min(bins - 1));
+
+    for (index, count) in histogram.counts.iter().enumerate() {
+        let height = plot.height() * *count as f32 / max_count as f32;
+        let left = plot.left() + index as f32 * bar_width;
+        let bar = Rect::from_min_max(
+            Pos2::new(left + 1.0, plot.bottom() - height),
+            Pos2::new(left + bar_width - 1.0, plot.bottom()),
+        );
+        let color = match hovered == Some(index) {
+            true => ui.visuals().selection.stroke.color,
+            false => ui.visuals().selection.bg_fill,
+        };
+        painter.rect_filled(bar, 0.0, color);
+    }
+
+    // Edge labels: first, middle and last.
+    for (index, align) in [
+        (0, Align2::LEFT_TOP),
+        (bins / 2, Align2::CENTER_TOP),
+        (bins, Align2::RIGHT_TOP),
+    ] {
+        let x = plot.left() + index as f32 * bar_width;
+        painter.text(
+            Pos2::new(x, plot.bottom() + 5.0),
+            align,
+            format_axis_value(histogram.edge(index), &histogram.dtype),
+            chart_font(),
+            text_color,
+        );
+    }
+
+    if let Some(index) = hovered {
+        let text = format!(
+            "{}: {}",
+            histogram.bin_label(index),
+            histogram.counts[index]
+        );
+        response.on_hover_text_at_pointer(text);
+    }
+}
+
+/// Paints the most frequent values as horizontal bars, labelled with the value and its count.
+fn paint_categories(ui: &mut Ui, categories: &[(String, usize)]) {
+    if categories.is_empty() {
+        ui.label("No values to display.");
+        return;
+    }
+
+    let row_height = 22.0;
+    let size = Vec2::new(ui.available_width(), row_height * categories.len() as f32);
+    let (response, painter) = ui.allocate_painter(size, Sense::hover());
+
+    let text_color = ui.visuals().text_color();
+    let max_count = categories
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    // The left third holds the labels, the rest the bars and their counts.
+    let label_width = response.rect.width() / 3.0;
+    let bar_space = response.rect.width() - label_width - 60.0;
+
+    let hovered = response
+        .hover_pos()
+        .map(|pos| ((pos.y - response.rect.top()) / row_height) as usize)
+        .filter(|index| *index < categories.len());
+
+    for (index, (value, count)) in categories.iter().enumerate() {
+        let top = response.rect.top() + index as f32 * row_height;
+        let center_y = top + row_height / 2.0;
+
+        let label_rect = Rect::from_min_size(
+            Pos2::new(response.rect.left(), top),
+            Vec2::new(label_width - 5.0, row_height),
+        );
+        painter.with_clip_rect(label_rect).text(
+            Pos2::new(label_rect.right(), center_y),
+            Align2::RIGHT_CENTER,
+            value,
+            chart_font(),
+            text_color,
+        );
+
+        let length = bar_space * *count as f32 / max_count as f32;
+        let bar = Rect::from_min_size(
+            Pos2::new(response.rect.left() + label_width, top + 2.0),
+            Vec2::new(length, row_height - 4.0),
+        );
+        let color = match hovered == Some(index) {
+            true => ui.visuals().selection.stroke.color,
+            false => ui.visuals().selection.bg_fill,
+        };
+        painter.rect_filled(bar, 0.0, color);
+        painter.text(
+            Pos2::new(bar.right() + 5.0, center_y),
+            Align2::LEFT_CENTER,
+            count.to_string(),
+            chart_font(),
+            text_color,
+        );
+    }
+
+    if let Some(index) = hovered {
+        let (value, count) = &categories[index];
+        response.on_hover_text_at_pointer(format!("{value}: {count}"));
+    }
+}
+
+/// Popover showing the distribution of one column, recomputed when the bin count changes.
+pub struct ColumnDistribution {
+    /// Name of the column.
+    column: String,
+    /// DataFrame the column belongs to.
+    df: Arc<DataFrame>,
+    /// Runtime used to recompute the distribution.
+    runtime: Handle,
+    /// Number of histogram bins (or categories) requested by the user.
+    bins: usize,
+    /// Distribution, once the background task has finished.
+    distribution: PendingTask<Distribution>,
+}
+
+impl ColumnDistribution {
+    /// Creates the popover and starts computing the distribution in the background.
+    pub fn new(runtime: &Handle, ctx: &Context, df: Arc<DataFrame>, column: String) -> Self {
+        let future = Distribution::compute(df.clone(), column.clone(), DEFAULT_BINS);
+        Self {
+            distribution: PendingTask::spawn(runtime, ctx, future),
+            column,
+            df,
+            runtime: runtime.clone(),
+            bins: DEFAULT_BINS,
+        }
+    }
+
+    /// Renders the bin count slider, returning its response.
+    fn bins_slider(&mut self, ui: &mut Ui) -> Response {
+        let label = match self.df.column(&self.column).map(|c| c.dtype()) {
+            Ok(dtype)
+                if dtype.is_primitive_numeric() || dtype.is_decimal() || dtype.is_temporal() =>
+            {
+                "Bins:"
+            }
+            _ => "Categories:",
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(label);
+            ui.add(Slider::new(&mut self.bins, 1..=100))
+        })
+        .inner
+    }
+}
+
+impl Popover for ColumnDistribution {
+    fn show(&mut self, ctx: &Context) -> bool {
+        let mut open = true;
+
+        egui::Window::new(format!("Distribution: {}", self.column))
+            .collapsible(false) // Make the window non-collapsible.
+            .resizable(true)
+            .default_size([600.0, 400.0])
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                let response = self.bins_slider(ui);
+
+                // Recompute once the user releases the slider, not on every dragged value.
+                if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                    let future =
+                        Distribution::compute(self.df.clone(), self.column.clone(), self.bins);
+                    self.distribution = PendingTask::spawn(&self.runtime, ctx, future);
+                }
+
+                ui.separator();
+
+                match self.distribution.poll() {
+                    None => {
+                        ui.spinner(); // The distribution is still being computed.
+                    }
+                    Some(Ok(distribution)) => distribution.paint(ui),
+                    Some(Err(message)) => {
+                        ui.label(format!("Error: {}", message));
+                    }
+                }
+            });
+
+        open // Return whether the window is open.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_counts() -> PolarsResult<()> {
+        let df = df!["value" => [Some(1), Some(2), Some(3), None, Some(4), Some(5), Some(6), Some(7), Some(8), Some(9), Some(10)]]?;
+        let histogram = Histogram::from_dataframe(&df, "value", 3)?;
+
+        assert_eq!(histogram.start, 1.0);
+        assert_eq!(histogram.width, 3.0);
+        assert_eq!(histogram.counts, vec![3, 3, 4]); // The maximum falls into the last bin.
+        assert_eq!(histogram.bin_label(0), "[1, 4)");
+        assert_eq!(histogram.bin_label(2), "[7, 10]");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_of_constant_column() -> PolarsResult<()> {
+        let df = df!["value" => [2.5, 2.5, 2.5]]?;
+        let histogram = Histogram::from_dataframe(&df, "value", 4)?;
+
+        assert_eq!(histogram.counts, vec![3, 0, 0, 0]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_axis_values() {
+        assert_eq!(format_axis_value(0.0, &DataType::Date), "1970-01-01");
+        assert_eq!(format_axis_value(18262.0, &DataType::Date), "2020-01-01");
+        assert_eq!(format_axis_value(2.5, &DataType::Float64), "2.50");
+        assert_eq!(format_axis_value(3.0, &DataType::Int32), "3");
+    }
+
+    #[test]
+    fn test_string_distribution_uses_categories() -> PolarsResult<()> {
+        let df = df!["kind" => ["Saída", "Entrada", "Saída"]]?;
+        let distribution = Distribution::from_dataframe(&df, "kind", 1)?;
+
+        assert_eq!(
+            distribution,
+            Distribution::Categories(vec![("Saída".to_string(), 2)])
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{fold, inspector::CellContent};
+
+use egui::{DragValue, Grid, Key, ScrollArea, TextEdit, Ui};
+use polars::prelude::*;
+
+/// State of the Record view, which shows one row at a time as a list of columns.
+#[derive(Debug, Default)]
+pub struct RecordView {
+    /// Position of the row shown, in the current data.
+    pub row: usize,
+    /// Text filtering the columns by name.
+    pub filter: String,
+}
+
+impl RecordView {
+    /// Renders the navigation bar and the row of `df`.
+    pub fn show(&mut self, ui: &mut Ui, df: &DataFrame) {
+        let height = df.height();
+        if height == 0 {
+            ui.label("No rows.");
+            return;
+        }
+        self.row = self.row.min(height - 1);
+
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("⏮").on_hover_text("First row").clicked() {
+                self.row = 0;
+            }
+            if ui.button("⏴").on_hover_text("Previous row (←)").clicked() {
+                self.row = self.row.saturating_sub(1);
+            }
+
+            // Rows are numbered from 1 in the UI.
+            let mut number = self.row + 1;
+            ui.add(DragValue::new(&mut number).range(1..=height));
+            self.row = number - 1;
+            ui.label(format!("of {height}"));
+
+            if ui.button("⏵").on_hover_text("Next row (→)").clicked() {
+                self.row = (self.row + 1).min(height - 1);
+            }
+            if ui.button("⏭").on_hover_text("Last row").clicked() {
+                self.row = height - 1;
+            }
+
+            ui.separator();
+            ui.label("Column:");
+            ui.add(
+                TextEdit::singleline(&mut self.filter)
+                    .hint_text("filter by name")
+                    .desired_width(200.0),
+            );
+        });
+
+        // Arrow keys move between rows, unless a text field has the focus.
+        if ui.memory(|memory| memory.focused().is_none()) {
+            if ui.input(|i| i.key_pressed(Key::ArrowLeft)) {
+                self.row = self.row.saturating_sub(1);
+            }
+            if ui.input(|i| i.key_pressed(Key::ArrowRight)) {
+                self.row = (self.row + 1).min(height - 1);
+            }
+        }
+        ui.separator();
+
+        let columns = matching_columns(df, &self.filter);
+
+        ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                Grid::new("record_grid")
+                    .num_columns(3)
+                    .spacing([20.0, 8.0])
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for column in columns.iter().map(|&index| &df.get_columns()[index]) {
+                            ui.label(column.name().as_str());
+                            ui.label(column.dtype().to_string());
+                            ui.vertical(|ui| match column.get(self.row) {
+                                Ok(value) => CellContent::new(value).render(ui),
+                                Err(error) => {
+                                    ui.label(format!("Error: {}", error));
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+/// Positions of the columns whose names contain `filter`, ignoring case and accents.
+pub fn matching_columns(df: &DataFrame, filter: &str) -> Vec<usize> {
+    let filter = fold(filter.trim());
+
+    df.get_column_names()
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| fold(name).contains(&filter))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matching_columns() -> PolarsResult<()> {
+        let df = df![
+            "Valor do Item" => [1],
+            "Alíquota de PIS" => [2],
+            "Valor de PIS" => [3],
+        ]?;
+
+        assert_eq!(matching_columns(&df, ""), vec![0, 1, 2]);
+        assert_eq!(matching_columns(&df, " pis "), vec![1, 2]);
+        assert_eq!(matching_columns(&df, "VALOR"), vec![0, 2]);
+        assert_eq!(matching_columns(&df, "aliquota"), vec![1]);
+        assert_eq!(matching_columns(&df, "ALÍQUOTA"), vec![1]);
+        assert!(matching_columns(&df, "cofins").is_empty());
+
+        Ok(())
+    }
+}
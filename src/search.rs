@@ -0,0 +1,355 @@
+use crate::{FormatSettings, components::TableState, data::PendingTask};
+
+use egui::{Key, Modifiers, TextEdit, Ui};
+use polars::prelude::*;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Options of a search, compared between frames to know when to search again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchOptions {
+    /// Text (or regular expression) to search for.
+    pub query: String,
+    /// Interpret the query as a regular expression.
+    pub regex: bool,
+    /// Distinguish upper and lower case letters.
+    pub match_case: bool,
+    /// Columns searched; all of them when empty.
+    pub columns: Vec<String>,
+}
+
+impl SearchOptions {
+    /// Builds the regular expression given to Polars.
+    pub fn pattern(&self) -> String {
+        let pattern = match self.regex {
+            true => self.query.clone(),
+            false => regex::escape(&self.query),
+        };
+
+        match self.match_case {
+            true => pattern,
+            false => format!("(?i){pattern}"),
+        }
+    }
+}
+
+/// Finds the cells matching the search, as (row, column) positions in row-major order.
+///
+/// Cells are searched by their text as the table shows it with `formats`.
+/// Nested and binary columns are not searched.
+pub fn find_matches(
+    df: &DataFrame,
+    options: &SearchOptions,
+    formats: &FormatSettings,
+) -> PolarsResult<Vec<(usize, usize)>> {
+    let columns: Vec<(usize, &Column)> = df
+        .get_columns()
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| !column.dtype().is_nested() && !column.dtype().is_binary())
+        .filter(|(_, column)| {
+            options.columns.is_empty()
+                || options.columns.iter().any(|c| c == column.name().as_str())
+        })
+        .collect();
+
+    let pattern = options.pattern();
+    let exprs: Vec<Expr> = columns
+        .iter()
+        .map(|(_, column)| {
+            formats
+                .resolve(column.name(), column.dtype())
+                .expr(column.name())
+                .str()
+                .contains(lit(pattern.clone()), true)
+                .fill_null(lit(false))
+        })
+        .collect();
+
+    let found = df.clone().lazy().select(exprs).collect()?;
+    let found = found
+        .get_columns()
+        .iter()
+        .map(|column| column.bool())
+        .collect::<PolarsResult<Vec<_>>>()?;
+
+    let mut matches = Vec::new();
+    for row in 0..df.height() {
+        for ((column_index, _), found) in columns.iter().zip(&found) {
+            if found.get(row) == Some(true) {
+                matches.push((row, *column_index));
+            }
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Positions of the matching cells, as (row, column).
+type Matches = Vec<(usize, usize)>;
+
+/// Ctrl+F search bar over the cells of the current table.
+#[derive(Default)]
+pub struct SearchBar {
+    /// Whether the bar is shown.
+    pub open: bool,
+    /// Options being edited.
+    pub options: SearchOptions,
+    /// Search running or finished, with the options, formats and data it was run on.
+    search: Option<(
+        SearchOptions,
+        FormatSettings,
+        Arc<DataFrame>,
+        PendingTask<Matches>,
+    )>,
+    /// Whether the finished search has been copied to the table state.
+    applied: bool,
+    /// Index of the current match.
+    current: usize,
+    /// Focus the text field on the next frame.
+    focus: bool,
+}
+
+impl SearchBar {
+    /// Opens the bar and focuses its text field.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.focus = true;
+    }
+
+    /// Renders the search bar above the table showing `df`, updating the highlights of `state`.
+    pub fn show(
+        &mut self,
+        ui: &mut Ui,
+        df: &Arc<DataFrame>,
+        runtime: &Handle,
+        state: &mut TableState,
+    ) {
+        if !self.open {
+            return;
+        }
+
+        let mut step: Option<isize> = None;
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Search:");
+            let response = ui.add(
+                TextEdit::singleline(&mut self.options.query)
+                    .hint_text("text or regex")
+                    .desired_width(250.0),
+            );
+            if std::mem::take(&mut self.focus) {
+                response.request_focus();
+            }
+
+            // Enter goes to the next match, Shift+Enter to the previous one.
+            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                step = match ui.input(|i| i.modifiers.shift) {
+                    true => Some(-1),
+                    false => Some(1),
+                };
+                response.request_focus();
+            }
+
+            ui.checkbox(&mut self.options.regex, "Regex");
+            ui.checkbox(&mut self.options.match_case, "Match case");
+
+            let columns_label = match self.options.columns.len() {
+                0 => "All columns".to_string(),
+                n => format!("{n} columns"),
+            };
+            ui.menu_button(columns_label, |ui| {
+                for name in df.get_column_names() {
+                    let mut checked = self.options.columns.iter().any(|c| c == name.as_str());
+                    if ui.checkbox(&mut checked, name.as_str()).changed() {
+                        match checked {
+                            true => self.options.columns.push(name.to_string()),
+                            false => self.options.columns.retain(|c| c != name.as_str()),
+                        }
+                    }
+                }
+            });
+
+            self.render_status(ui);
+
+            if ui
+                .button("⏶")
+                .on_hover_text("Previous match (Shift+Enter)")
+                .clicked()
+            {
+                step = Some(-1);
+            }
+            if ui.button("⏷").on_hover_text("Next match (Enter)").clicked() {
+                step = Some(1);
+            }
+            if ui.button("✖").on_hover_text("Close (Esc)").clicked()
+                || ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Escape))
+            {
+                self.open = false;
+            }
+        });
+        ui.separator();
+
+        if !self.open {
+            self.search = None;
+            clear_highlights(state);
+            return;
+        }
+
+        self.update(ui, df, runtime, state);
+
+        if let Some(step) = step {
+            self.navigate(step, state);
+        }
+    }
+
+    /// Shows the number of matches, or the search error.
+    fn render_status(&mut self, ui: &mut Ui) {
+        match self.search.as_mut().map(|(_, _, _, task)| task.poll()) {
+            None => {}
+            Some(None) => {
+                ui.spinner(); // The search is still running.
+            }
+            Some(Some(Err(message))) => {
+                ui.label(message.as_str());
+            }
+            Some(Some(Ok(matches))) if matches.is_empty() => {
+                ui.label("No matches");
+            }
+            Some(Some(Ok(matches))) => {
+                ui.label(format!("{} of {} matches", self.current + 1, matches.len()));
+            }
+        }
+    }
+
+    /// Starts a new search when the options or the data changed, and highlights the results.
+    fn update(&mut self, ui: &Ui, df: &Arc<DataFrame>, runtime: &Handle, state: &mut TableState) {
+        let up_to_date = matches!(&self.search, Some((options, formats, searched, _))
+            if *options == self.options && *formats == state.formats && Arc::ptr_eq(searched, df));
+
+        if !up_to_date {
+            clear_highlights(state);
+            self.search = None;
+
+            if !self.options.query.is_empty() {
+                let (data, options) = (df.clone(), self.options.clone());
+                let formats = state.formats.clone();
+                let future = async move {
+                    find_matches(&data, &options, &formats)
+                        .map_err(|e| format!("Search error: {}", e))
+                };
+                let task = PendingTask::spawn(runtime, ui.ctx(), future);
+                let formats = state.formats.clone();
+                self.search = Some((self.options.clone(), formats, df.clone(), task));
+                self.applied = false;
+            }
+        }
+
+        if self.applied {
+            return;
+        }
+
+        if let Some(Some(Ok(matches))) = self.search.as_mut().map(|(_, _, _, task)| task.poll()) {
+            state.matches = matches.iter().copied().collect();
+            self.applied = true;
+            self.current = 0;
+            self.navigate(0, state);
+        }
+    }
+
+    /// Moves `step` matches forward (or backward) and scrolls the table to the match.
+    fn navigate(&mut self, step: isize, state: &mut TableState) {
+        let Some(Some(Ok(matches))) = self.search.as_mut().map(|(_, _, _, task)| task.poll())
+        else {
+            return;
+        };
+        if matches.is_empty() {
+            return;
+        }
+
+        let count = matches.len() as isize;
+        self.current = (self.current as isize + step).rem_euclid(count) as usize;
+
+        let position = matches[self.current];
+        state.current_match = Some(position);
+        state.scroll_to_row = Some(position.0);
+    }
+}
+
+/// Removes the search highlights from the table.
+fn clear_highlights(state: &mut TableState) {
+    state.matches.clear();
+    state.current_match = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(query: &str) -> SearchOptions {
+        SearchOptions {
+            query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_are_row_major() -> PolarsResult<()> {
+        let df = df![
+            "name" => ["Alpha", "beta", "ALPHA"],
+            "note" => [Some("alphabet"), None, Some("gamma")],
+        ]?;
+        let formats = FormatSettings::default();
+
+        assert_eq!(
+            find_matches(&df, &options("alpha"), &formats)?,
+            vec![(0, 0), (0, 1), (2, 0)]
+        );
+
+        let match_case = SearchOptions {
+            match_case: true,
+            ..options("alpha")
+        };
+        assert_eq!(find_matches(&df, &match_case, &formats)?, vec![(0, 1)]);
+
+        let only_name = SearchOptions {
+            columns: vec!["name".to_string()],
+            ..options("alpha")
+        };
+        assert_eq!(
+            find_matches(&df, &only_name, &formats)?,
+            vec![(0, 0), (2, 0)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_literal_and_regex() -> PolarsResult<()> {
+        let df = df!["value" => [1.5, 10.0, 125.0]]?;
+        let formats = FormatSettings::default();
+
+        // A literal dot is not a wildcard.
+        assert_eq!(find_matches(&df, &options("1.5"), &formats)?, vec![(0, 0)]);
+
+        // Cells are searched as shown: floats with two decimals by default.
+        assert_eq!(
+            find_matches(&df, &options("10.00"), &formats)?,
+            vec![(1, 0)]
+        );
+
+        let regex = SearchOptions {
+            regex: true,
+            ..options("^1.5")
+        };
+        assert_eq!(find_matches(&df, &regex, &formats)?, vec![(0, 0), (2, 0)]);
+
+        let invalid = SearchOptions {
+            regex: true,
+            ..options("(")
+        };
+        assert!(find_matches(&df, &invalid, &formats).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use polars::prelude::*;
+use std::{collections::HashSet, ops::Range};
+
+/// Cells selected in the table: a rectangle between the anchor and the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// Cell where the selection started, as (row, column).
+    pub anchor: (usize, usize),
+    /// Cell where the selection ends, as (row, column).
+    pub cursor: (usize, usize),
+    /// Every column of the selected rows is selected.
+    pub whole_rows: bool,
+    /// Every row of the selected columns is selected.
+    pub whole_columns: bool,
+}
+
+impl Selection {
+    /// Selects a single cell.
+    pub fn cell(row: usize, column: usize) -> Self {
+        Self {
+            anchor: (row, column),
+            cursor: (row, column),
+            whole_rows: false,
+            whole_columns: false,
+        }
+    }
+
+    /// Selects the whole row of a cell.
+    pub fn row(row: usize, column: usize) -> Self {
+        Self {
+            whole_rows: true,
+            ..Self::cell(row, column)
+        }
+    }
+
+    /// Selects a whole column.
+    pub fn column(column: usize) -> Self {
+        Self {
+            whole_columns: true,
+            ..Self::cell(0, column)
+        }
+    }
+
+    /// Moves the cursor, keeping the anchor: the selection becomes a range.
+    pub fn extend(&mut self, row: usize, column: usize) {
+        self.cursor = (row, column);
+    }
+
+    /// Selected rows of a table with `height` rows.
+    ///
+    /// A selection beyond the table, made on other data, gives an empty range.
+    pub fn rows(&self, height: usize) -> Range<usize> {
+        match self.whole_columns {
+            true => 0..height,
+            false => {
+                let (a, b) = (self.anchor.0, self.cursor.0);
+                a.min(b).min(height)..(a.max(b) + 1).min(height)
+            }
+        }
+    }
+
+    /// Selected columns of a table with `width` columns.
+    ///
+    /// A selection beyond the table, made on other data, gives an empty range.
+    pub fn columns(&self, width: usize) -> Range<usize> {
+        match self.whole_rows {
+            true => 0..width,
+            false => {
+                let (a, b) = (self.anchor.1, self.cursor.1);
+                a.min(b).min(width)..(a.max(b) + 1).min(width)
+            }
+        }
+    }
+
+    /// Checks whether a cell is selected.
+    pub fn contains(&self, row: usize, column: usize) -> bool {
+        self.rows(usize::MAX).contains(&row) && self.columns(usize::MAX).contains(&column)
+    }
+}
+
+/// Text formats the selection can be copied as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// Tab separated values, optionally with a header line; pastes into spreadsheets.
+    Tsv { headers: bool },
+    /// Markdown table with a header line.
+    Markdown,
+    /// Distinct values of the selection, as the list of a SQL `IN` clause.
+    SqlInList,
+}
+
+/// Formats the selected cells of `df` as text.
+pub fn copy_selection(
+    df: &DataFrame,
+    selection: &Selection,
+    format: CopyFormat,
+) -> PolarsResult<String> {
+    let rows = selection.rows(df.height());
+    let columns = &df.get_columns()[selection.columns(df.width())];
+
+    let cells = |row: usize| -> PolarsResult<Vec<AnyValue>> {
+        columns.iter().map(|column| column.get(row)).collect()
+    };
+
+    let text = match format {
+        CopyFormat::Tsv { headers } => {
+            let mut lines = Vec::new();
+            if headers {
+                lines.push(join(
+                    columns.iter().map(|c| c.name().to_string()),
+                    "\t",
+                    tsv_field,
+                ));
+            }
+            for row in rows {
+                let values = cells(row)?.into_iter().map(cell_text);
+                lines.push(join(values, "\t", tsv_field));
+            }
+            lines.join("\n")
+        }
+        CopyFormat::Markdown => {
+            let line = |values: Vec<String>| format!("| {} |", join(values, " | ", markdown_field));
+            let mut lines = vec![
+                line(columns.iter().map(|c| c.name().to_string()).collect()),
+                format!("|{}", "---|".repeat(columns.len())),
+            ];
+            for row in rows {
+                lines.push(line(cells(row)?.into_iter().map(cell_text).collect()));
+            }
+            lines.join("\n")
+        }
+        CopyFormat::SqlInList => {
+            let mut seen = HashSet::new();
+            let mut values = Vec::new();
+            for row in rows {
+                for value in cells(row)? {
+                    let literal = sql_literal(&value);
+                    if seen.insert(literal.clone()) {
+                        values.push(literal);
+                    }
+                }
+            }
+            format!("({})", values.join(", "))
+        }
+    };
+
+    Ok(text)
+}
+
+/// Joins values escaped by `escape` with a separator.
+fn join(
+    values: impl IntoIterator<Item = String>,
+    separator: &str,
+    escape: fn(&str) -> String,
+) -> String {
+    values
+        .into_iter()
+        .map(|value| escape(&value))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Text of a cell, with nothing for nulls.
+pub fn cell_text(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => String::new(),
+        value => value
+            .get_str()
+            .map(String::from)
+            .unwrap_or_else(|| value.to_string()),
+    }
+}
+
+/// Quotes a TSV field containing tabs, line breaks or quotes, as spreadsheets expect.
+fn tsv_field(value: &str) -> String {
+    match value.contains(['\t', '\n', '\r', '"']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Escapes the characters that would break a Markdown table cell.
+fn markdown_field(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Writes a value as a SQL literal: numbers and booleans as they are, text between single quotes.
+fn sql_literal(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Null => "NULL".to_string(),
+        AnyValue::Boolean(_) => value.to_string(),
+        value if value.dtype().is_primitive_numeric() => value.to_string(),
+        value => format!("'{}'", cell_text(value.clone()).replace('\'', "''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PolarsResult<DataFrame> {
+        df![
+            "name" => [Some("Entrada"), Some("d'Água"), None],
+            "value" => [1.5, 2.0, 1.5],
+            "note" => ["a|b", "tab\there", "x"],
+        ]
+    }
+
+    #[test]
+    fn test_selection_ranges() {
+        let mut selection = Selection::cell(5, 3);
+        selection.extend(2, 1);
+        assert_eq!(selection.rows(10), 2..6);
+        assert_eq!(selection.columns(10), 1..4);
+        assert!(selection.contains(2, 3));
+        assert!(!selection.contains(6, 3));
+
+        assert_eq!(Selection::row(4, 2).columns(7), 0..7);
+        assert_eq!(Selection::column(2).rows(9), 0..9);
+        assert!(Selection::column(2).contains(1000, 2));
+    }
+
+    #[test]
+    fn test_copy_tsv() -> PolarsResult<()> {
+        let df = sample()?;
+        let mut selection = Selection::cell(0, 1);
+        selection.extend(1, 2);
+
+        let tsv = copy_selection(&df, &selection, CopyFormat::Tsv { headers: false })?;
+        assert_eq!(tsv, "1.5\ta|b\n2.0\t\"tab\there\"");
+
+        let tsv = copy_selection(
+            &df,
+            &Selection::row(2, 0),
+            CopyFormat::Tsv { headers: true },
+        )?;
+        assert_eq!(tsv, "name\tvalue\tnote\n\t1.5\tx");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_stale_selection() -> PolarsResult<()> {
+        // Selected on wider and longer data, then copied from the result of a query.
+        let mut selection = Selection::cell(5, 5);
+        selection.extend(6, 6);
+        let narrow = df!["a" => [1, 2], "b" => [3, 4]]?;
+
+        assert_eq!(selection.columns(narrow.width()), 2..2);
+        let tsv = copy_selection(&narrow, &selection, CopyFormat::Tsv { headers: true })?;
+        assert_eq!(tsv, "");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_markdown() -> PolarsResult<()> {
+        let df = sample()?;
+        let mut selection = Selection::cell(0, 0);
+        selection.extend(0, 2);
+
+        let markdown = copy_selection(&df, &selection, CopyFormat::Markdown)?;
+        assert_eq!(
+            markdown,
+            "| name | value | note |\n|---|---|---|\n| Entrada | 1.5 | a\\|b |"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_sql_in_list() -> PolarsResult<()> {
+        let df = sample()?;
+
+        let names = copy_selection(&df, &Selection::column(0), CopyFormat::SqlInList)?;
+        assert_eq!(names, "('Entrada', 'd''Água', NULL)");
+
+        let values = copy_selection(&df, &Selection::column(1), CopyFormat::SqlInList)?;
+        assert_eq!(values, "(1.5, 2.0)");
+
+        Ok(())
+    }
+}
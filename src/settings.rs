@@ -0,0 +1,245 @@
+use crate::FormatSettings;
+
+use egui::{CollapsingHeader, DragValue, Grid, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+
+/// Key of the settings in the eframe storage.
+const SETTINGS_KEY: &str = "settings";
+
+/// Options used to read CSV files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CsvOptions {
+    /// Delimiters tried in order until one splits the file into several columns.
+    pub delimiters: String,
+    /// Number of rows used to infer the type of the columns.
+    pub infer_schema_length: usize,
+    /// Parse the text that looks like dates as dates.
+    pub try_parse_dates: bool,
+    /// Values read as null.
+    pub null_values: Vec<String>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiters: ",;|\t".to_string(),
+            infer_schema_length: 200,
+            try_parse_dates: true,
+            null_values: ["", " ", "<N/D>", "*DIVERSOS*"].map(String::from).to_vec(),
+        }
+    }
+}
+
+/// Sizes of the text styles, in points.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FontSizes {
+    pub small: f32,
+    pub body: f32,
+    pub monospace: f32,
+    pub button: f32,
+    pub heading: f32,
+}
+
+impl Default for FontSizes {
+    fn default() -> Self {
+        Self {
+            small: 12.0,
+            body: 16.0,
+            monospace: 14.0,
+            button: 14.0,
+            heading: 14.0,
+        }
+    }
+}
+
+/// User preferences, saved with eframe and restored at startup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Dark theme, or light.
+    pub dark_mode: bool,
+    pub font_sizes: FontSizes,
+    /// Show the text (not only the code) in a monospace font.
+    pub monospace: bool,
+    /// Space between widgets, in points.
+    pub item_spacing: f32,
+    /// Name of the loaded data in SQL queries.
+    pub table_name: String,
+    pub csv: CsvOptions,
+    pub formats: FormatSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            font_sizes: FontSizes::default(),
+            monospace: false,
+            item_spacing: 8.0,
+            table_name: "AllData".to_string(),
+            csv: CsvOptions::default(),
+            formats: FormatSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Restores the settings saved by a previous session, or the default ones.
+    pub fn load(storage: Option<&dyn eframe::Storage>) -> Self {
+        storage
+            .and_then(|storage| eframe::get_value(storage, SETTINGS_KEY))
+            .unwrap_or_default()
+    }
+
+    /// Saves the settings for the next session.
+    pub fn save(&self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, self);
+    }
+
+    /// Renders the settings editor.
+    ///
+    /// Returns true when the settings changed.
+    pub fn render(&mut self, ui: &mut Ui) -> bool {
+        let before = self.clone();
+
+        CollapsingHeader::new("Appearance")
+            .default_open(true)
+            .show(ui, |ui| self.render_appearance(ui));
+
+        CollapsingHeader::new("Data")
+            .default_open(true)
+            .show(ui, |ui| self.render_data(ui));
+
+        CollapsingHeader::new("Formatting").show(ui, |ui| {
+            self.formats.render_editor(ui);
+        });
+
+        ui.separator();
+        if ui.button("Reset to defaults").clicked() {
+            *self = Settings::default();
+        }
+
+        *self != before
+    }
+
+    /// Renders the theme, font and spacing settings.
+    fn render_appearance(&mut self, ui: &mut Ui) {
+        Grid::new("appearance_settings")
+            .num_columns(2)
+            .spacing([20.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Theme:");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.dark_mode, true, "🌙 Dark");
+                    ui.selectable_value(&mut self.dark_mode, false, "☀ Light");
+                });
+                ui.end_row();
+
+                ui.label("Font:");
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.monospace, false, "Proportional");
+                    ui.selectable_value(&mut self.monospace, true, "Monospace");
+                });
+                ui.end_row();
+
+                let sizes = &mut self.font_sizes;
+                for (label, size) in [
+                    ("Body size:", &mut sizes.body),
+                    ("Small size:", &mut sizes.small),
+                    ("Monospace size:", &mut sizes.monospace),
+                    ("Button size:", &mut sizes.button),
+                    ("Heading size:", &mut sizes.heading),
+                ] {
+                    ui.label(label);
+                    ui.add(DragValue::new(size).range(8.0..=32.0).speed(0.25));
+                    ui.end_row();
+                }
+
+                ui.label("Item spacing:");
+                ui.add(
+                    DragValue::new(&mut self.item_spacing)
+                        .range(0.0..=20.0)
+                        .speed(0.25),
+                );
+                ui.end_row();
+            });
+    }
+
+    /// Renders the table name and CSV settings.
+    fn render_data(&mut self, ui: &mut Ui) {
+        Grid::new("data_settings")
+            .num_columns(2)
+            .spacing([20.0, 6.0])
+            .show(ui, |ui| {
+                ui.label("Default table name:");
+                ui.add(TextEdit::singleline(&mut self.table_name).desired_width(150.0));
+                ui.end_row();
+
+                // The tab is shown (and typed) as \t.
+                ui.label("CSV delimiters:");
+                let mut delimiters = self.csv.delimiters.replace('\t', "\\t");
+                let response = ui.add(TextEdit::singleline(&mut delimiters).desired_width(150.0));
+                if response.changed() {
+                    self.csv.delimiters = delimiters.replace("\\t", "\t");
+                }
+                ui.end_row();
+
+                ui.label("Rows to infer types:");
+                ui.add(DragValue::new(&mut self.csv.infer_schema_length).range(1..=1_000_000));
+                ui.end_row();
+
+                ui.label("Parse dates:");
+                ui.checkbox(&mut self.csv.try_parse_dates, "");
+                ui.end_row();
+
+                ui.label("Null markers:");
+                ui.vertical(|ui| {
+                    let mut removed: Option<usize> = None;
+                    for (index, value) in self.csv.null_values.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                TextEdit::singleline(value)
+                                    .hint_text("empty")
+                                    .desired_width(120.0),
+                            );
+                            if ui.small_button("🗑").on_hover_text("Remove").clicked() {
+                                removed = Some(index);
+                            }
+                        });
+                    }
+                    if let Some(index) = removed {
+                        self.csv.null_values.remove(index);
+                    }
+                    if ui.small_button("Add").clicked() {
+                        self.csv.null_values.push(String::new());
+                    }
+                });
+                ui.end_row();
+            });
+
+        ui.label("CSV options apply to the files opened from now on.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_use_defaults() -> Result<(), String> {
+        // Settings saved by an older version lack the newer fields.
+        let settings: Settings =
+            serde_json::from_str(r#"{"dark_mode": false, "csv": {"delimiters": ";"}}"#)
+                .map_err(|e| e.to_string())?;
+
+        assert!(!settings.dark_mode);
+        assert_eq!(settings.csv.delimiters, ";");
+        assert_eq!(settings.csv.infer_schema_length, 200);
+        assert_eq!(settings.table_name, "AllData");
+        assert_eq!(settings.formats, FormatSettings::default());
+
+        Ok(())
+    }
+}
@@ -2,56 +2,367 @@
 // https://docs.pola.rs/api/python/stable/reference/sql/clauses.html
 // https://docs.pola.rs/api/python/stable/reference/sql/functions/index.html
 
-const GROUP_BY_DATE: &str = "\
-SELECT
-    `Período de Apuração`,
-    `Ano do Período de Apuração`,
-    `Mês do Período de Apuração`,
-    `Tipo de Operação`,
-    `Tipo de Crédito`,
-    SUM(`Valor da Base de Cálculo das Contribuições`) AS Base_de_Calculo,
-    SUM(`Valor de PIS/PASEP`) AS Pis,
-    SUM(`Valor de COFINS`) AS Cofins
-FROM
-    AllData
-GROUP BY
-    `Período de Apuração`,
-    `Ano do Período de Apuração`,
-    `Mês do Período de Apuração`,
-    `Tipo de Operação`,
-    `Tipo de Crédito`
-ORDER BY
-    `Período de Apuração`,
-    CASE `Tipo de Operação`
-        WHEN 'Saída' THEN 1
-        WHEN 'Entrada' THEN 2
-        WHEN 'Detalhamento' THEN 3
-        WHEN 'Descontos' THEN 4
-        ELSE 5  -- Lidar com outros valores inesperados
-    END;\
-";
-
-const DATE_FORMAT: &str = "\
-SELECT
-    AllData.*,
-    STRFTIME(`Período de Apuração`, '%d/%m/%Y') AS `PA (day/month/year)`
-FROM
-    AllData;\
-";
-
-// Predefined SQL commands for easy selection.
-pub const SQL_COMMANDS: [&str; 13] = [
-    "SELECT * FROM AllData;",
-    "SELECT `Valor da Base de Cálculo das Contribuições` FROM AllData;",
-    "SELECT * FROM AllData WHERE `Data 1ª DCOMP Ativa` IS NOT NULL;",
-    "SELECT * FROM AllData WHERE `Ano do Período de Apuração` = 2020;",
-    "SELECT * FROM AllData WHERE `Mês do Período de Apuração` IS NULL;",
-    "SELECT * FROM AllData WHERE `Mês do Período de Apuração` = '';",
-    "SELECT * FROM AllData WHERE `Ano do Período de Apuração` = 2020 AND `Mês do Período de Apuração` = 'março';",
-    "SELECT * FROM AllData WHERE `Ano do Período de Apuração` = 2020 AND `Trimestre do Período de Apuração` = 3;",
-    "SELECT `Tipo de Crédito`, COUNT(*) AS Frequencia FROM AllData GROUP BY `Tipo de Crédito` ORDER BY Frequencia DESC;",
-    "SELECT `Tipo de Operação`, `Tipo de Crédito`, COUNT(*) FROM AllData GROUP BY `Tipo de Operação`, `Tipo de Crédito`;",
-    "SELECT * FROM AllData WHERE `Natureza da Base de Cálculo dos Créditos` LIKE 'Saldo de Crédito%' AND `Mês do Período de Apuração` IS NULL;",
-    DATE_FORMAT,
-    GROUP_BY_DATE,
-];
+use crate::{TokenKind, tokenize};
+
+use egui::{Hyperlink, TextEdit, Ui};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, path::PathBuf};
+
+/// Placeholder of the saved queries, replaced with the table name of the current tab.
+pub const TABLE_PLACEHOLDER: &str = "{table}";
+
+/// Name of the query library file, in the storage directory of the application.
+const LIBRARY_FILE: &str = "queries.json";
+
+/// A named query of the library.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// The query, where `{table}` stands for the table name.
+    pub sql: String,
+}
+
+impl SavedQuery {
+    fn new(name: &str, description: &str, sql: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            sql: sql.to_string(),
+        }
+    }
+
+    /// The query, with `{table}` replaced by the table name, quoted if needed.
+    pub fn resolve(&self, table_name: &str) -> String {
+        self.sql
+            .replace(TABLE_PLACEHOLDER, &quote_table(table_name))
+    }
+}
+
+/// The table name as written in a query: in backticks unless it is a plain name,
+/// such as one with spaces, a `-` or a keyword.
+fn quote_table(table_name: &str) -> String {
+    match tokenize(table_name).as_slice() {
+        [token] if token.kind == TokenKind::Identifier && token.range.len() == table_name.len() => {
+            table_name.to_string()
+        }
+        _ => format!("`{}`", table_name.replace('`', "``")),
+    }
+}
+
+/// Replaces the table name by `{table}` in a query, so that it can be saved for any table.
+///
+/// Only whole names are replaced, not the text of strings or of other names.
+pub fn generalize(sql: &str, table_name: &str) -> String {
+    let quoted = format!("`{table_name}`");
+
+    tokenize(sql)
+        .into_iter()
+        .map(|token| {
+            let text = &sql[token.range];
+            let name = matches!(
+                token.kind,
+                TokenKind::Identifier | TokenKind::QuotedIdentifier
+            );
+            match name && (text == table_name || text == quoted) {
+                true => TABLE_PLACEHOLDER,
+                false => text,
+            }
+        })
+        .collect()
+}
+
+/// Action requested from the query library.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryAction {
+    /// Put this query in the editor.
+    Insert(String),
+    /// Add the queries of a file chosen by the user.
+    Import,
+    /// Write the library to a file chosen by the user.
+    Export,
+    /// The queries were added, deleted, or an edit was finished.
+    Changed,
+}
+
+/// Queries saved by the user, stored in a JSON file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryLibrary {
+    pub queries: Vec<SavedQuery>,
+}
+
+impl Default for QueryLibrary {
+    fn default() -> Self {
+        Self {
+            queries: vec![
+                SavedQuery::new(
+                    "All rows",
+                    "Every row and column.",
+                    "SELECT * FROM {table};",
+                ),
+                SavedQuery::new(
+                    "First rows",
+                    "The first 100 rows.",
+                    "SELECT * FROM {table} LIMIT 100;",
+                ),
+                SavedQuery::new(
+                    "Row count",
+                    "Number of rows.",
+                    "SELECT COUNT(*) AS total FROM {table};",
+                ),
+                SavedQuery::new(
+                    "Distinct rows",
+                    "Rows without repetitions.",
+                    "SELECT DISTINCT * FROM {table};",
+                ),
+            ],
+        }
+    }
+}
+
+impl QueryLibrary {
+    /// Path of the library file, in the storage directory of the application.
+    pub fn path() -> Option<PathBuf> {
+        eframe::storage_dir("PolarsView").map(|dir| dir.join(LIBRARY_FILE))
+    }
+
+    /// Loads the library file, or the default queries if there is none yet.
+    pub fn load() -> Result<Self, String> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Saves the library file.
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path().ok_or("No directory to save the queries.")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Error creating {:?}: {}", dir, e))?;
+        }
+        self.save_to(&path)
+    }
+
+    /// Reads a library from a JSON file.
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let json =
+            fs::read_to_string(path).map_err(|e| format!("Error reading {:?}: {}", path, e))?;
+        serde_json::from_str(&json).map_err(|e| format!("Invalid query library {:?}: {}", path, e))
+    }
+
+    /// Writes the library to a JSON file.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Error writing {:?}: {}", path, e))
+    }
+
+    /// Adds the queries of another library that are not in this one yet, by their SQL
+    /// and description.
+    ///
+    /// An imported query named as a different saved query is renamed, "Name (2)".
+    pub fn import(&mut self, other: QueryLibrary) {
+        for mut query in other.queries {
+            let saved = self
+                .queries
+                .iter()
+                .any(|saved| saved.sql == query.sql && saved.description == query.description);
+            if saved {
+                continue;
+            }
+            if self.has_name(&query.name) {
+                query.name = (2..)
+                    .map(|number| format!("{} ({number})", query.name))
+                    .find(|name| !self.has_name(name))
+                    .unwrap_or_default();
+            }
+            self.queries.push(query);
+        }
+    }
+
+    /// Adds a query, named "Query N" with the first number not used yet, and returns its position.
+    pub fn add(&mut self, sql: String) -> usize {
+        let name = (self.queries.len() + 1..)
+            .map(|number| format!("Query {number}"))
+            .find(|name| !self.has_name(name))
+            .unwrap_or_default();
+        self.queries.push(SavedQuery::new(&name, "", &sql));
+        self.queries.len() - 1
+    }
+
+    /// Checks whether a saved query has this name.
+    fn has_name(&self, name: &str) -> bool {
+        self.queries.iter().any(|query| query.name == name)
+    }
+
+    /// Renders the library: insert, edit and delete the queries, save the current one,
+    /// import and export.
+    ///
+    /// `current` is the query of the editor, on the table `table_name`.
+    pub fn render(
+        &mut self,
+        ui: &mut Ui,
+        current: &str,
+        table_name: &str,
+    ) -> Option<LibraryAction> {
+        let mut action: Option<LibraryAction> = None;
+        let editing_id = ui.id().with("editing_query");
+        let mut editing: Option<usize> = ui.data(|data| data.get_temp(editing_id)).flatten();
+        let edited = editing;
+
+        ui.horizontal_wrapped(|ui| {
+            if ui
+                .button("Save current query")
+                .on_hover_text("The table name is saved as {table}.")
+                .clicked()
+            {
+                editing = Some(self.add(generalize(current, table_name)));
+                action = Some(LibraryAction::Changed);
+            }
+            if ui.button("Import...").clicked() {
+                action = Some(LibraryAction::Import);
+            }
+            if ui.button("Export...").clicked() {
+                action = Some(LibraryAction::Export);
+            }
+        });
+        ui.separator();
+
+        let mut removed: Option<usize> = None;
+        for (index, query) in self.queries.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    let sql = query.resolve(table_name);
+                    if ui.button("Insert").on_hover_text(&sql).clicked() {
+                        action = Some(LibraryAction::Insert(sql));
+                    }
+                    ui.strong(&query.name);
+
+                    let editing_this = editing == Some(index);
+                    if ui
+                        .selectable_label(editing_this, "✏")
+                        .on_hover_text("Edit")
+                        .clicked()
+                    {
+                        editing = (!editing_this).then_some(index);
+                    }
+                    if ui.button("🗑").on_hover_text("Delete").clicked() {
+                        removed = Some(index);
+                    }
+                });
+                if !query.description.is_empty() {
+                    ui.weak(&query.description);
+                }
+
+                if editing == Some(index) {
+                    let responses = [
+                        ui.add(TextEdit::singleline(&mut query.name).hint_text("Name")),
+                        ui.add(
+                            TextEdit::singleline(&mut query.description).hint_text("Description"),
+                        ),
+                        ui.add(
+                            TextEdit::multiline(&mut query.sql)
+                                .code_editor()
+                                .hint_text("SELECT * FROM {table};"),
+                        ),
+                    ];
+                    // Saved when a field is left, not on every key.
+                    if responses.iter().any(|response| response.lost_focus()) {
+                        action.get_or_insert(LibraryAction::Changed);
+                    }
+                }
+            });
+            ui.separator();
+        }
+
+        if let Some(index) = removed {
+            self.queries.remove(index);
+            editing = None;
+            action = Some(LibraryAction::Changed);
+        }
+
+        // Closing the edit of a query saves it, too.
+        if edited.is_some() && editing != edited {
+            action.get_or_insert(LibraryAction::Changed);
+        }
+
+        ui.data_mut(|data| data.insert_temp(editing_id, editing));
+
+        let url = "https://docs.pola.rs/api/python/stable/reference/sql/index.html";
+        ui.add(Hyperlink::from_label_and_url("Polars SQL Interface", url))
+            .on_hover_text(url);
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generalize_and_resolve() {
+        let sql = "SELECT `notas`.*, 'notas' AS origem FROM notas JOIN notas_itens ON true";
+        let saved = generalize(sql, "notas");
+        assert_eq!(
+            saved,
+            "SELECT {table}.*, 'notas' AS origem FROM {table} JOIN notas_itens ON true"
+        );
+
+        let query = SavedQuery::new("Test", "", &saved);
+        assert_eq!(
+            query.resolve("AllData"),
+            "SELECT AllData.*, 'notas' AS origem FROM AllData JOIN notas_itens ON true"
+        );
+
+        // Names that are not plain identifiers are quoted, as `generalize` accepts them.
+        let query = SavedQuery::new("Test", "", "SELECT * FROM {table}");
+        assert_eq!(query.resolve("Notas 2024"), "SELECT * FROM `Notas 2024`");
+        assert_eq!(query.resolve("notas-itens"), "SELECT * FROM `notas-itens`");
+        assert_eq!(query.resolve("select"), "SELECT * FROM `select`");
+        assert_eq!(
+            generalize(&query.resolve("Notas 2024"), "Notas 2024"),
+            query.sql
+        );
+    }
+
+    #[test]
+    fn test_add_after_delete() {
+        let mut library = QueryLibrary::default();
+        let count = library.queries.len();
+        library.add("SELECT 1;".to_string());
+        library.queries.remove(0);
+
+        let index = library.add("SELECT 2;".to_string());
+        let names: Vec<&str> = library.queries.iter().map(|q| q.name.as_str()).collect();
+        assert_eq!(names[index], format!("Query {}", count + 2));
+        assert_eq!(
+            names
+                .iter()
+                .filter(|name| name.starts_with("Query"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_import_keeps_both() {
+        let mut library = QueryLibrary::default();
+        let count = library.queries.len();
+
+        let mut shared = QueryLibrary {
+            queries: vec![SavedQuery::new("All rows", "Changed", "SELECT 1;")],
+        };
+        shared.add("SELECT * FROM {table} WHERE x > 0;".to_string());
+        library.import(shared.clone());
+        assert_eq!(library.queries.len(), count + 2);
+        assert_eq!(library.queries[0], QueryLibrary::default().queries[0]);
+        assert_eq!(library.queries[count].name, "All rows (2)");
+        assert_eq!(library.queries[count + 1].name, "Query 2");
+
+        // Importing the same queries again adds nothing.
+        let before = library.clone();
+        library.import(shared);
+        assert_eq!(library, before);
+    }
+}
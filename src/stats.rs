@@ -0,0 +1,372 @@
+use crate::{
+    Popover,
+    data::{DataFilters, DataFrameContainer, PendingTask},
+};
+
+use egui::{Color32, Context, Frame, Grid, Stroke, Ui};
+use polars::prelude::*;
+use std::sync::Arc;
+
+/// Number of most frequent values listed for string columns.
+const TOP_VALUES: usize = 10;
+
+/// Descriptive statistics of a single DataFrame column.
+#[derive(Debug, Clone)]
+pub struct ColumnStats {
+    /// Column name.
+    pub name: String,
+    /// Column data type.
+    pub dtype: DataType,
+    /// Number of rows, including nulls.
+    pub count: usize,
+    /// Number of null values.
+    pub null_count: usize,
+    /// Number of distinct values (null counts as a value).
+    pub distinct_count: usize,
+    /// Smallest value, for orderable columns (numbers, dates, strings, booleans).
+    pub min: Option<String>,
+    /// Largest value, for orderable columns (numbers, dates, strings, booleans).
+    pub max: Option<String>,
+    /// Moments and quantiles, for numeric columns only.
+    pub numeric: Option<NumericStats>,
+    /// Most frequent non-null values and their counts, for string columns only.
+    pub top_values: Vec<(String, usize)>,
+}
+
+/// Moments and quantiles of a numeric column.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NumericStats {
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+    pub q25: Option<f64>,
+    pub median: Option<f64>,
+    pub q75: Option<f64>,
+}
+
+impl ColumnStats {
+    /// Computes the statistics of a column in the background.
+    pub async fn compute(df: Arc<DataFrame>, name: String) -> Result<Self, String> {
+        Self::from_dataframe(&df, &name).map_err(|e| format!("Polars statistics error: {}", e))
+    }
+
+    /// Computes the statistics of the column `name` of `df`.
+    pub fn from_dataframe(df: &DataFrame, name: &str) -> PolarsResult<Self> {
+        let dtype = df.column(name)?.dtype().clone();
+
+        // Work on a single column frame, so that Polars never touches the other columns.
+        let lazyframe = df.select([name])?.lazy();
+
+        let basic = lazyframe
+            .clone()
+            .select([
+                len().alias("count"),
+                col(name).null_count().alias("null_count"),
+                col(name).n_unique().alias("distinct_count"),
+            ])
+            .collect()?;
+
+        let (min, max) = if dtype.is_ord() && !dtype.is_binary() {
+            let extremes = lazyframe
+                .clone()
+                .select([col(name).min().alias("min"), col(name).max().alias("max")])
+                .collect()?;
+            (
+                value_to_string(extremes.column("min")?.get(0)?),
+                value_to_string(extremes.column("max")?.get(0)?),
+            )
+        } else {
+            (None, None)
+        };
+
+        let numeric = if dtype.is_primitive_numeric() || dtype.is_decimal() {
+            Some(numeric_stats(lazyframe.clone(), name)?)
+        } else {
+            None
+        };
+
+        let top_values = if dtype.is_string() || dtype.is_categorical() {
+            top_values(lazyframe, name, TOP_VALUES)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            dtype,
+            count: get_usize(&basic, "count")?,
+            null_count: get_usize(&basic, "null_count")?,
+            distinct_count: get_usize(&basic, "distinct_count")?,
+            min,
+            max,
+            numeric,
+            top_values,
+        })
+    }
+
+    /// Renders the statistics as a two column grid.
+    pub fn render(&self, ui: &mut Ui) {
+        let format_float = |value: Option<f64>| match value {
+            Some(value) => format!("{value:0.4}"),
+            None => "".to_string(),
+        };
+
+        Grid::new(format!("column_stats_{}", self.name))
+            .num_columns(2)
+            .spacing([10.0, 8.0])
+            .striped(true)
+            .show(ui, |ui| {
+                let mut row = |label: &str, value: String| {
+                    ui.label(label);
+                    ui.label(value);
+                    ui.end_row();
+                };
+
+                row("Type:", self.dtype.to_string());
+                row("Rows:", self.count.to_string());
+                row("Nulls:", self.null_count.to_string());
+                row("Distinct:", self.distinct_count.to_string());
+
+                if let (Some(min), Some(max)) = (&self.min, &self.max) {
+                    row("Min:", min.clone());
+                    row("Max:", max.clone());
+                }
+
+                if let Some(numeric) = &self.numeric {
+                    row("Mean:", format_float(numeric.mean));
+                    row("Std:", format_float(numeric.std));
+                    row("25%:", format_float(numeric.q25));
+                    row("Median:", format_float(numeric.median));
+                    row("75%:", format_float(numeric.q75));
+                }
+            });
+
+        if !self.top_values.is_empty() {
+            ui.separator();
+            ui.label(format!("Top {} values:", self.top_values.len()));
+            Grid::new(format!("column_top_values_{}", self.name))
+                .num_columns(2)
+                .spacing([10.0, 4.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for (value, count) in &self.top_values {
+                        ui.label(value);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+}
+
+impl DataFrameContainer {
+    /// Builds a `describe()`-like summary, with one row per column of the DataFrame.
+    pub async fn summarize(self) -> Result<Self, String> {
+        let df = summary(&self.df).map_err(|e| format!("Polars summary error: {}", e))?;
+
+        Ok(Self::new(self.filename, df, DataFilters::default()))
+    }
+}
+
+/// Computes dtype, count, nulls, null %, unique, min, max, mean and median of every column.
+pub fn summary(df: &DataFrame) -> PolarsResult<DataFrame> {
+    let stats = df
+        .get_column_names()
+        .into_iter()
+        .map(|name| ColumnStats::from_dataframe(df, name))
+        .collect::<PolarsResult<Vec<ColumnStats>>>()?;
+
+    let null_percent = |s: &ColumnStats| match s.count {
+        0 => None,
+        count => Some((10_000.0 * s.null_count as f64 / count as f64).round() / 100.0),
+    };
+    let numeric =
+        |s: &ColumnStats, f: fn(&NumericStats) -> Option<f64>| s.numeric.as_ref().and_then(f);
+
+    df!(
+        "column" => stats.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+        "dtype" => stats.iter().map(|s| s.dtype.to_string()).collect::<Vec<_>>(),
+        "count" => stats.iter().map(|s| s.count as u64).collect::<Vec<_>>(),
+        "nulls" => stats.iter().map(|s| s.null_count as u64).collect::<Vec<_>>(),
+        "null %" => stats.iter().map(null_percent).collect::<Vec<_>>(),
+        "unique" => stats.iter().map(|s| s.distinct_count as u64).collect::<Vec<_>>(),
+        "min" => stats.iter().map(|s| s.min.clone()).collect::<Vec<_>>(),
+        "max" => stats.iter().map(|s| s.max.clone()).collect::<Vec<_>>(),
+        "mean" => stats.iter().map(|s| numeric(s, |n| n.mean)).collect::<Vec<_>>(),
+        "median" => stats.iter().map(|s| numeric(s, |n| n.median)).collect::<Vec<_>>(),
+    )
+}
+
+/// Computes mean, standard deviation and quartiles of a numeric column.
+fn numeric_stats(lazyframe: LazyFrame, name: &str) -> PolarsResult<NumericStats> {
+    let values = col(name).cast(DataType::Float64);
+    let quantile = |q: f64| values.clone().quantile(lit(q), QuantileMethod::Linear);
+
+    let df = lazyframe
+        .select([
+            values.clone().mean().alias("mean"),
+            values.clone().std(1).alias("std"),
+            quantile(0.25).alias("q25"),
+            values.clone().median().alias("median"),
+            quantile(0.75).alias("q75"),
+        ])
+        .collect()?;
+
+    let get = |stat: &str| -> PolarsResult<Option<f64>> { Ok(df.column(stat)?.get(0)?.extract()) };
+
+    Ok(NumericStats {
+        mean: get("mean")?,
+        std: get("std")?,
+        q25: get("q25")?,
+        median: get("median")?,
+        q75: get("q75")?,
+    })
+}
+
+/// Lists the `n` most frequent non-null values of a column, most frequent first.
+pub fn top_values(
+    lazyframe: LazyFrame,
+    name: &str,
+    n: usize,
+) -> PolarsResult<Vec<(String, usize)>> {
+    let sort_options = SortMultipleOptions::default()
+        .with_order_descending_multi([true, false])
+        .with_maintain_order(true);
+
+    let df = lazyframe
+        .filter(col(name).is_not_null())
+        .group_by([col(name)])
+        .agg([len().alias("count")])
+        .sort(
+            [PlSmallStr::from("count"), PlSmallStr::from(name)],
+            sort_options,
+        )
+        .limit(n as IdxSize)
+        .collect()?;
+
+    let values = df.column(name)?;
+    let counts = df.column("count")?;
+
+    (0..df.height())
+        .map(|row| {
+            let value = value_to_string(values.get(row)?).unwrap_or_default();
+            let count = counts.get(row)?.extract::<usize>().unwrap_or_default();
+            Ok((value, count))
+        })
+        .collect()
+}
+
+/// Reads the first value of a count column.
+fn get_usize(df: &DataFrame, name: &str) -> PolarsResult<usize> {
+    Ok(df
+        .column(name)?
+        .get(0)?
+        .extract::<usize>()
+        .unwrap_or_default())
+}
+
+/// Converts a value to a display string, returning `None` for nulls.
+fn value_to_string(value: AnyValue) -> Option<String> {
+    match value {
+        AnyValue::Null => None,
+        av => match av.get_str() {
+            Some(s) => Some(s.to_string()), // Owned or borrowed strings, without quotes.
+            None => Some(av.to_string()),
+        },
+    }
+}
+
+/// Popover showing the statistics of one column, computed on the tokio runtime.
+pub struct ColumnProfile {
+    /// Name of the profiled column.
+    pub column: String,
+    /// Statistics, once the background task has finished.
+    pub stats: PendingTask<ColumnStats>,
+}
+
+impl Popover for ColumnProfile {
+    fn show(&mut self, ctx: &Context) -> bool {
+        let mut open = true;
+
+        egui::Window::new(format!("Column Profile: {}", self.column))
+            .collapsible(false) // Make the window non-collapsible.
+            .open(&mut open) // Control the window's open state.
+            .show(ctx, |ui| {
+                Frame::default()
+                    .stroke(Stroke::new(1.0, Color32::GRAY))
+                    .inner_margin(10.0)
+                    .show(ui, |ui| match self.stats.poll() {
+                        None => {
+                            ui.spinner(); // Statistics are still being computed.
+                        }
+                        Some(Ok(stats)) => stats.render(ui),
+                        Some(Err(message)) => {
+                            ui.label(format!("Error: {}", message));
+                        }
+                    });
+            });
+
+        open // Return whether the window is open.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_column_stats() -> PolarsResult<()> {
+        let df = df!["value" => [Some(1.0), Some(2.0), None, Some(3.0), Some(4.0)]]?;
+        let stats = ColumnStats::from_dataframe(&df, "value")?;
+
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.distinct_count, 5);
+        assert_eq!(stats.min.as_deref(), Some("1.0"));
+        assert_eq!(stats.max.as_deref(), Some("4.0"));
+
+        let numeric = stats.numeric.expect("numeric statistics");
+        assert_eq!(numeric.mean, Some(2.5));
+        assert_eq!(numeric.median, Some(2.5));
+        assert_eq!(numeric.q25, Some(1.75));
+        assert_eq!(numeric.q75, Some(3.25));
+        assert!(stats.top_values.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_has_one_row_per_column() -> PolarsResult<()> {
+        let df = df![
+            "kind" => [Some("Saída"), None, Some("Entrada"), Some("Saída")],
+            "value" => [1, 2, 3, 6],
+        ]?;
+        let summary = summary(&df)?;
+
+        assert_eq!(summary.height(), 2);
+        assert_eq!(summary.column("column")?.get(1)?, AnyValue::String("value"));
+        assert_eq!(summary.column("null %")?.get(0)?, AnyValue::Float64(25.0));
+        assert_eq!(summary.column("unique")?.get(0)?, AnyValue::UInt64(3));
+        assert_eq!(summary.column("mean")?.get(0)?, AnyValue::Null);
+        assert_eq!(summary.column("mean")?.get(1)?, AnyValue::Float64(3.0));
+        assert_eq!(summary.column("median")?.get(1)?, AnyValue::Float64(2.5));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_column_top_values() -> PolarsResult<()> {
+        let df = df!["kind" => [Some("Saída"), Some("Entrada"), Some("Saída"), None]]?;
+        let stats = ColumnStats::from_dataframe(&df, "kind")?;
+
+        assert_eq!(stats.null_count, 1);
+        assert_eq!(stats.min.as_deref(), Some("Entrada"));
+        assert_eq!(stats.max.as_deref(), Some("Saída"));
+        assert!(stats.numeric.is_none());
+        assert_eq!(
+            stats.top_values,
+            vec![("Saída".to_string(), 2), ("Entrada".to_string(), 1)]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,580 @@
+use crate::{
+    ChartView, ColumnLayout, Completion, CompletionKind, ConditionalRule, ErrorLocation,
+    FilterEditor, FormatSettings, HistoryEntry, QueryParams, RecordView, SearchBar, View,
+    components::{FileMetadata, TableState, render_polars_schema},
+    data::{
+        CreatedTable, DataFilters, DataFrameContainer, DataFuture, PendingTask, QueryPlan,
+        SqlTable, spawn_task,
+    },
+};
+
+use egui::{Context, Id, TextEdit, Ui};
+use polars::prelude::DataFrame;
+use std::{future::Future, path::Path, sync::Arc, time::Instant};
+use tokio::{
+    runtime::Handle,
+    sync::oneshot::{Receiver, error::TryRecvError},
+    task::JoinHandle,
+};
+
+/// An open document: a file, the data queried from it and the state of its views.
+#[derive(Default)]
+pub struct Tab {
+    /// The loaded data (Parquet, CSV, etc.).
+    pub table: Arc<Option<DataFrameContainer>>,
+    /// Filters (SQL query, sorting, etc.) applied to the file.
+    pub data_filters: DataFilters,
+    /// Metadata extracted from the loaded file (if available).
+    pub metadata: Option<Arc<FileMetadata>>,
+    /// View displayed in the central panel.
+    pub view: View,
+    /// Summary of the data, computed when the Summary view is first shown.
+    pub summary: Option<PendingTask<DataFrameContainer>>,
+    /// Chart configuration and data of the Chart view.
+    pub chart: ChartView,
+    /// Filter editor of a column, with the view whose data it filters.
+    pub filter_editor: Option<(View, FilterEditor)>,
+    /// Row shown by the Record view.
+    pub record: RecordView,
+    /// Ctrl+F search over the cells of the table.
+    pub search: SearchBar,
+    /// Highlights and scrolling of the Table view.
+    pub table_state: TableState,
+    /// Highlights and scrolling of the Summary view.
+    pub summary_state: TableState,
+    /// Visible columns of the data, cached with the data and layout they come from.
+    pub layout_view: Option<(Arc<DataFrame>, ColumnLayout, Arc<DataFrame>)>,
+    /// Values of the `:name` and `{{name}}` parameters of the query.
+    pub params: QueryParams,
+    /// Plan of the query, shown by the Explain window.
+    pub plan: Option<PendingTask<QueryPlan>>,
+    /// Location of the last SQL error in the query, underlined by the editor.
+    pub query_error: Option<ErrorLocation>,
+    /// Channel receiving the data being loaded, sorted or queried.
+    pub pipe: Option<Receiver<Result<DataFrameContainer, String>>>,
+    /// Queries finished since the history last took them.
+    pub executed: Vec<HistoryEntry>,
+    /// Query being run, with the time it started.
+    running_query: Option<(HistoryEntry, Instant)>,
+    /// Background tasks of the tab.
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl Tab {
+    /// Creates an empty tab formatting its tables with the given rules.
+    pub fn new(formats: &FormatSettings, rules: &[ConditionalRule]) -> Self {
+        let mut tab = Tab::default();
+        tab.table_state.formats = formats.clone();
+        tab.table_state.conditional.rules = rules.to_vec();
+        tab.summary_state.formats = formats.clone();
+        tab
+    }
+
+    /// Name shown by the tab bar: the name of the file, without its directory.
+    pub fn title(&self) -> String {
+        match self.table.as_ref() {
+            Some(table) => file_name(&table.filename),
+            None if self.pipe.is_some() => "Loading...".to_string(),
+            None => "New tab".to_string(),
+        }
+    }
+
+    /// Whether the tab has no data, nor any being loaded.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_none() && self.pipe.is_none()
+    }
+
+    /// Spawns a Tokio task running `future` and returns the channel that will receive its result.
+    pub fn spawn_task<T: Send + 'static>(
+        &mut self,
+        runtime: &Handle,
+        future: impl Future<Output = Result<T, String>> + Send + 'static,
+        ctx: &Context,
+    ) -> Receiver<Result<T, String>> {
+        // Before scheduling a new future, ensure no tasks are stuck
+        self.tasks.retain(|task| !task.is_finished());
+
+        let (rx, handle) = spawn_task(runtime, ctx, future);
+
+        self.tasks.push(handle); // Track the task.
+        rx
+    }
+
+    /// Loads, sorts or queries the data of the tab in the background.
+    pub fn run_data_future(&mut self, runtime: &Handle, future: DataFuture, ctx: &Context) {
+        self.pipe = Some(self.spawn_task(runtime, future, ctx));
+        self.running_query = None; // Sorting and filtering are not queries.
+    }
+
+    /// Runs a SQL query in the background, recording it in `executed` when it finishes.
+    pub fn run_query(&mut self, runtime: &Handle, filters: DataFilters, ctx: &Context) {
+        let entry = HistoryEntry::new(&filters);
+
+        // A query on another file reads its metadata too, in the background.
+        let same_source =
+            (self.table.as_ref().as_ref()).is_some_and(|table| table.same_source(&filters));
+        let new_source = !same_source || self.metadata.is_none();
+        let future = async move {
+            let data = DataFrameContainer::load_data_with_sql(filters).await?;
+            Ok(match new_source {
+                true => data.with_metadata(None),
+                false => data,
+            })
+        };
+        self.run_data_future(runtime, Box::new(Box::pin(future)), ctx);
+        self.running_query = Some((entry, Instant::now()));
+    }
+
+    /// Records the outcome of the query being run, if any: its number of rows or its error.
+    ///
+    /// Returns whether the finished task was a query.
+    fn finish_query(&mut self, outcome: Result<usize, &str>) -> bool {
+        match self.running_query.take() {
+            Some((entry, started)) => {
+                self.executed.push(entry.finish(started.elapsed(), outcome));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Clears what depends on the positions of the visible columns.
+    pub fn layout_changed(&mut self) {
+        self.table_state.selection = None;
+        self.summary = None; // The summary describes the visible columns.
+    }
+
+    /// Returns the data with a column layout applied: visible columns, in display order.
+    pub fn apply_layout(
+        &mut self,
+        data: &DataFrameContainer,
+        layout: ColumnLayout,
+    ) -> DataFrameContainer {
+        let cached = match &self.layout_view {
+            Some((df, cached_layout, view))
+                if Arc::ptr_eq(df, &data.df) && *cached_layout == layout =>
+            {
+                Some(view.clone())
+            }
+            _ => None,
+        };
+
+        let view = cached.unwrap_or_else(|| {
+            // A layout naming unknown columns only keeps the known ones, so this cannot fail.
+            let view = Arc::new(
+                layout
+                    .apply(&data.df)
+                    .unwrap_or_else(|_| data.df.as_ref().clone()),
+            );
+            self.layout_view = Some((data.df.clone(), layout.clone(), view.clone()));
+            view
+        });
+
+        self.table_state.pinned = layout.pinned_count(&data.df);
+
+        DataFrameContainer {
+            df: view,
+            ..data.clone()
+        }
+    }
+
+    /// Checks if there is a data loading operation pending (asynchronous).
+    ///
+    /// The data keeps the table name of the tab, or gets `default_table_name`. The query of
+    /// the editor is kept after a query, a sort or a filter, and only replaced by
+    /// `SELECT * FROM <table>;` when another file is loaded.
+    /// Returns `Ok(true)` if data is still loading, `Ok(false)` otherwise, and the error
+    /// of the loading process, if any.
+    pub fn check_data_pending(&mut self, default_table_name: &str) -> Result<bool, String> {
+        // Take the receiver out of the `Option`.  This allows us to check if the data has arrived.
+        let Some(mut output) = self.pipe.take() else {
+            return Ok(false); // No data loading in progress.
+        };
+
+        match output.try_recv() {
+            Ok(Err(message)) => {
+                // An error occurred during data loading.  Its location in the query run, whose
+                // parameters were substituted, is mapped back to the query of the editor.
+                let query = self.data_filters.query.as_deref().unwrap_or_default();
+                self.query_error = ErrorLocation::parse(&message)
+                    .and_then(|location| self.params.error_location(query, location));
+                self.finish_query(Err(&message));
+                Err(message)
+            }
+            Ok(Ok(data)) => {
+                // Data loaded successfully!
+                self.query_error = None;
+                let queried = self.finish_query(Ok(data.df.height()));
+                let filename = data.filename.clone();
+                let table_name = match self.data_filters.table_name.as_deref() {
+                    Some(name) if !name.trim().is_empty() => name.to_string(),
+                    _ => default_table_name.to_string(),
+                };
+
+                // Create data filters
+                let table_name = table_name.as_str();
+                let mut data_filters = DataFilters::new(&filename, table_name);
+                if let Some(delimiter) = &data.filters.csv_delimiter {
+                    data_filters.csv_delimiter = Some(delimiter.to_string())
+                }
+                data_filters.csv_options = data.filters.csv_options.clone();
+
+                // Keep the query being written (its parameters, its other statements).
+                let same_file =
+                    (self.table.as_ref().as_ref()).is_some_and(|table| table.filename == filename);
+                if queried || same_file {
+                    if let Some(query) = self.data_filters.query.take() {
+                        data_filters.query = Some(query);
+                    }
+                }
+                self.data_filters = data_filters;
+
+                // The metadata is read by the task that loads a file or queries another one,
+                // since sorting and querying keep the same file.
+                let same_source = (self.table.as_ref().as_ref())
+                    .is_some_and(|table| table.same_source(&data.filters));
+                if let Some(metadata) = &data.metadata {
+                    self.metadata = Some(metadata.clone());
+                } else if !same_source {
+                    self.metadata = None;
+                }
+                self.table = Arc::new(Some(data));
+                self.summary = None; // The summary describes the previous data.
+                // The selected cells and the search matches are positions in the previous data.
+                self.table_state.selection = None;
+                self.table_state.matches.clear();
+                self.table_state.current_match = None;
+                self.chart.reset();
+                Ok(false) // Data loading complete.
+            }
+            Err(TryRecvError::Empty) => {
+                // Data is still loading. Put the receiver back into the `Option`.
+                self.pipe = Some(output);
+                Ok(true)
+            }
+            // The data loading task was terminated unexpectedly.
+            Err(TryRecvError::Closed) => {
+                let message = "Data operation terminated without response.";
+                self.finish_query(Err(message));
+                Err(message.into())
+            }
+        }
+    }
+}
+
+/// Name of a file, without its directory.
+pub(crate) fn file_name(filename: &str) -> String {
+    Path::new(filename)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string())
+}
+
+/// Action on the tabs requested from the tab bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabAction {
+    /// Show the tab at this position.
+    Select(usize),
+    /// Close the tab at this position.
+    Close(usize),
+    /// Move the tab at the first position to the second one.
+    Move(usize, usize),
+}
+
+/// The open tabs, one of which is shown.
+pub struct Tabs {
+    pub list: Vec<Tab>,
+    /// Position of the tab shown.
+    pub active: usize,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self {
+            list: vec![Tab::default()],
+            active: 0,
+        }
+    }
+}
+
+impl Tabs {
+    /// The tab shown.
+    pub fn active(&self) -> &Tab {
+        &self.list[self.active]
+    }
+
+    /// The tab shown, for changes.
+    pub fn active_mut(&mut self) -> &mut Tab {
+        &mut self.list[self.active]
+    }
+
+    /// Adds a tab after the others and shows it.
+    pub fn open(&mut self, tab: Tab) {
+        self.list.push(tab);
+        self.active = self.list.len() - 1;
+    }
+
+    /// Closes a tab, showing its neighbour if it was the one shown.
+    ///
+    /// The list is left empty when the last tab is closed.
+    pub fn close(&mut self, index: usize) {
+        if index >= self.list.len() {
+            return;
+        }
+
+        self.list.remove(index);
+        if index < self.active || self.active >= self.list.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+    }
+
+    /// Moves the tab at position `from` to position `to`, keeping the same tab shown.
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        if from >= self.list.len() || to >= self.list.len() {
+            return;
+        }
+
+        let tab = self.list.remove(from);
+        self.list.insert(to, tab);
+
+        self.active = match self.active {
+            active if active == from => to,
+            active if from < active && active <= to => active - 1,
+            active if to <= active && active < from => active + 1,
+            active => active,
+        };
+    }
+
+    /// Table name given to the data of a tab: `base`, numbered when another tab uses it.
+    pub fn default_table_name(&self, index: usize, base: &str) -> String {
+        let taken: Vec<&str> = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, tab)| tab.data_filters.table_name.as_deref())
+            .collect();
+
+        (1..)
+            .map(|number| match number {
+                1 => base.to_string(),
+                _ => format!("{base}{number}"),
+            })
+            .find(|name| !taken.contains(&name.as_str()))
+            .unwrap_or_default()
+    }
+
+    /// Files of the tabs other than `index`, registered as tables of its queries.
+    pub fn sql_tables(&self, index: usize) -> Vec<SqlTable> {
+        self.list
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(_, tab)| {
+                let table = tab.table.as_ref().as_ref()?;
+                Some(SqlTable {
+                    name: tab.data_filters.table_name.clone()?,
+                    filename: table.filename.clone(),
+                    csv_delimiter: tab.data_filters.csv_delimiter.clone(),
+                    csv_options: tab.data_filters.csv_options.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Names suggested by the SQL editor of the tab shown: the tables, the columns of its
+    /// data, the keywords and the functions.
+    pub fn completions(&self) -> Vec<Completion> {
+        let tables = self
+            .list
+            .iter()
+            .filter(|tab| tab.table.is_some())
+            .filter_map(|tab| tab.data_filters.table_name.as_ref())
+            .chain(self.created_tables().map(|table| &table.name))
+            .map(|name| Completion::new(CompletionKind::Table, name));
+
+        let columns = self
+            .active()
+            .table
+            .as_ref()
+            .iter()
+            .flat_map(|table| table.df.get_column_names_owned())
+            .map(|name| Completion::new(CompletionKind::Column, name));
+
+        tables.chain(columns).chain(Completion::sql()).collect()
+    }
+
+    /// Tables created by the statements of the query of the current tab.
+    fn created_tables(&self) -> impl Iterator<Item = &CreatedTable> {
+        self.active()
+            .table
+            .as_ref()
+            .iter()
+            .flat_map(|table| &table.created_tables)
+    }
+
+    /// Renders the tables of the SQL queries: the editable name and the schema of each open
+    /// file, then the tables created by the query of the current tab.
+    pub fn render_tables(&mut self, ui: &mut Ui) {
+        let names: Vec<Option<String>> = self
+            .list
+            .iter()
+            .map(|tab| tab.data_filters.table_name.clone())
+            .collect();
+
+        for (index, tab) in self.list.iter_mut().enumerate() {
+            let (Some(table), Some(name)) = (tab.table.as_ref(), &mut tab.data_filters.table_name)
+            else {
+                continue;
+            };
+
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(TextEdit::singleline(name).desired_width(120.0))
+                        .on_hover_text(&table.filename);
+                    ui.label(file_name(&table.filename));
+                });
+
+                let duplicated = names
+                    .iter()
+                    .enumerate()
+                    .any(|(other, other_name)| other != index && other_name.as_ref() == Some(name));
+                if duplicated {
+                    let message = "Another tab has this name; the queried tab takes it.";
+                    ui.colored_label(ui.visuals().warn_fg_color, message);
+                }
+
+                ui.collapsing("Schema", |ui| match &tab.metadata {
+                    Some(metadata) => metadata.render_schema(ui),
+                    None => table.render_schema(ui),
+                });
+            });
+            ui.separator();
+        }
+
+        for (index, table) in self.created_tables().enumerate() {
+            ui.push_id(("created", index), |ui| {
+                ui.label(&table.name)
+                    .on_hover_text("Created by the query (CREATE TABLE ... AS)");
+                ui.collapsing("Schema", |ui| render_polars_schema(&table.schema, ui));
+            });
+            ui.separator();
+        }
+    }
+
+    /// Renders the tab bar: click to show a tab, drag to reorder, ✖ to close.
+    pub fn render_bar(&self, ui: &mut Ui) -> Option<TabAction> {
+        let mut action: Option<TabAction> = None;
+
+        ui.horizontal_wrapped(|ui| {
+            for (index, tab) in self.list.iter().enumerate() {
+                let id = Id::new(("tab", index));
+                let response = ui
+                    .dnd_drag_source(id, index, |ui| {
+                        ui.horizontal(|ui| {
+                            let label = ui.selectable_label(index == self.active, tab.title());
+                            let label = match tab.table.as_ref() {
+                                Some(table) => label.on_hover_text(&table.filename),
+                                None => label,
+                            };
+                            if label.clicked() {
+                                action = Some(TabAction::Select(index));
+                            }
+                            if ui.small_button("✖").on_hover_text("Close").clicked() {
+                                action = Some(TabAction::Close(index));
+                            }
+                        });
+                    })
+                    .response;
+
+                // Dropping a tab on another one moves it there.
+                if let Some(from) = response.dnd_release_payload::<usize>() {
+                    action = Some(TabAction::Move(*from, index));
+                }
+                ui.separator();
+            }
+        });
+
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tabs with `count` empty tabs, showing the one at `active`.
+    fn tabs(count: usize, active: usize) -> Tabs {
+        Tabs {
+            list: (0..count).map(|_| Tab::default()).collect(),
+            active,
+        }
+    }
+
+    #[test]
+    fn test_move_keeps_the_active_tab() {
+        let mut tabs = tabs(4, 1);
+
+        tabs.move_tab(1, 3); // The active tab itself.
+        assert_eq!(tabs.active, 3);
+
+        tabs.move_tab(0, 2); // From before to after the active tab.
+        assert_eq!(tabs.active, 3);
+
+        tabs.move_tab(3, 0);
+        tabs.move_tab(2, 0); // From after to before the active tab.
+        assert_eq!(tabs.active, 1);
+    }
+
+    #[test]
+    fn test_default_table_names_are_unique() {
+        let mut tabs = tabs(3, 0);
+        tabs.list[0].data_filters.table_name = Some("AllData".to_string());
+        tabs.list[1].data_filters.table_name = Some("AllData2".to_string());
+
+        assert_eq!(tabs.default_table_name(0, "AllData"), "AllData");
+        assert_eq!(tabs.default_table_name(2, "AllData"), "AllData3");
+        assert_eq!(tabs.default_table_name(2, "Notas"), "Notas");
+    }
+
+    #[test]
+    fn test_close() {
+        let mut tabs = tabs(3, 2);
+
+        tabs.close(2); // The active tab, which is the last one.
+        assert_eq!((tabs.list.len(), tabs.active), (2, 1));
+
+        tabs.close(0); // A tab before the active one.
+        assert_eq!((tabs.list.len(), tabs.active), (1, 0));
+
+        tabs.close(0);
+        assert!(tabs.list.is_empty());
+    }
+
+    #[test]
+    fn test_query_is_kept_after_it_runs() -> Result<(), String> {
+        let script = "CREATE TABLE t AS SELECT * FROM notas;\nSELECT * FROM t WHERE ano = :year";
+        let filters = DataFilters {
+            query: Some(script.to_string()),
+            ..DataFilters::new("notas.csv", "notas")
+        };
+        let mut tab = Tab {
+            data_filters: filters.clone(),
+            running_query: Some((HistoryEntry::new(&filters), Instant::now())),
+            ..Default::default()
+        };
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tab.pipe = Some(receiver);
+        let df = polars::df!["ano" => [2020]].map_err(|e| e.to_string())?;
+        let data = DataFrameContainer::new("notas.csv".to_string(), df, filters);
+        sender.send(Ok(data)).map_err(|_| "Receiver dropped")?;
+
+        assert_eq!(tab.check_data_pending("AllData"), Ok(false));
+        assert_eq!(tab.data_filters.query.as_deref(), Some(script));
+        assert_eq!(tab.data_filters.table_name.as_deref(), Some("notas"));
+        assert_eq!(tab.executed.len(), 1); // Recorded in the history.
+
+        Ok(())
+    }
+}
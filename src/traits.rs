@@ -1,42 +1,50 @@
-use crate::SortState;
+use crate::{Settings, SortState};
 
 use egui::{
     Context,
-    FontFamily::Proportional,
+    FontFamily::{Monospace as MonospaceFamily, Proportional},
     FontId, Response,
     TextStyle::{Body, Button, Heading, Monospace, Small},
-    Ui, WidgetText,
+    Theme, Ui, WidgetText,
 };
 
 /// A trait for applying custom styling to the egui context.
 pub trait MyStyle {
-    /// Sets the initial style for the egui context.
-    fn set_style_init(&self);
+    /// Sets the style for the egui context from the user settings.
+    fn set_style_init(&self, settings: &Settings);
 }
 
 impl MyStyle for Context {
     /// Specifies the look and feel of egui.
     ///
     /// <https://docs.rs/egui/latest/egui/style/struct.Style.html>
-    fn set_style_init(&self) {
-        // Get current context style
-        let mut style = (*self.style()).clone();
-
-        // Redefine text_styles
-        style.text_styles = [
-            (Small, FontId::new(12.0, Proportional)),
-            (Body, FontId::new(16.0, Proportional)),
-            (Monospace, FontId::new(14.0, Proportional)),
-            (Button, FontId::new(14.0, Proportional)),
-            (Heading, FontId::new(14.0, Proportional)),
-        ]
-        .into();
-
-        style.spacing.item_spacing.x = 8.0;
-        style.spacing.item_spacing.y = 6.0;
-
-        // Mutate global style with above changes
-        self.set_style(style);
+    fn set_style_init(&self, settings: &Settings) {
+        // The theme chosen in the settings; fonts and spacing apply to both themes.
+        self.set_theme(match settings.dark_mode {
+            true => Theme::Dark,
+            false => Theme::Light,
+        });
+
+        let family = match settings.monospace {
+            true => MonospaceFamily,
+            false => Proportional,
+        };
+        let sizes = &settings.font_sizes;
+
+        self.all_styles_mut(|style| {
+            // Redefine text_styles; code is always monospaced.
+            style.text_styles = [
+                (Small, FontId::new(sizes.small, family.clone())),
+                (Body, FontId::new(sizes.body, family.clone())),
+                (Monospace, FontId::new(sizes.monospace, MonospaceFamily)),
+                (Button, FontId::new(sizes.button, family.clone())),
+                (Heading, FontId::new(sizes.heading, family.clone())),
+            ]
+            .into();
+
+            style.spacing.item_spacing.x = settings.item_spacing;
+            style.spacing.item_spacing.y = settings.item_spacing * 0.75;
+        });
     }
 }
 
@@ -45,26 +53,6 @@ pub trait Popover {
     fn show(&mut self, ctx: &Context) -> bool;
 }
 
-// Settings popover struct (currently disabled).
-pub struct Settings {}
-
-impl Popover for Settings {
-    fn show(&mut self, ctx: &Context) -> bool {
-        let mut open = true;
-
-        // Create a window named "Settings".
-        egui::Window::new("Settings")
-            .collapsible(false) // Make the window non-collapsible.
-            .open(&mut open) // Control the window's open state.
-            .show(ctx, |ui| {
-                ctx.style_ui(ui, egui::Theme::Dark); // Apply dark theme.
-                ui.disable(); // Disable user interaction.
-            });
-
-        open // Return whether the window is open.
-    }
-}
-
 // Error popover struct.
 pub struct Error {
     pub message: String,